@@ -0,0 +1,115 @@
+// Objective-style aggregates of many variables' errors per iteration, for judging a calibration as
+// a whole rather than one line at a time.
+
+use crate::run::Run;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateMetric {
+    Rmse,
+    MeanAbs,
+    MaxAbs,
+    Sse,
+}
+
+impl AggregateMetric {
+    pub const ALL: [AggregateMetric; 4] = [AggregateMetric::Rmse, AggregateMetric::MeanAbs, AggregateMetric::MaxAbs, AggregateMetric::Sse];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AggregateMetric::Rmse => "RMSE",
+            AggregateMetric::MeanAbs => "Mean |Error|",
+            AggregateMetric::MaxAbs => "Max |Error|",
+            AggregateMetric::Sse => "SSE",
+        }
+    }
+}
+
+/// Which variables the aggregates are taken over
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateScope {
+    #[default]
+    All,
+    Filtered, // Variables matching the filter box
+    Selected,
+}
+
+impl AggregateScope {
+    pub const ALL: [AggregateScope; 3] = [AggregateScope::All, AggregateScope::Filtered, AggregateScope::Selected];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AggregateScope::All => "All variables",
+            AggregateScope::Filtered => "Filtered variables",
+            AggregateScope::Selected => "Selected variables",
+        }
+    }
+}
+
+/// What the aggregate panel shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregateView {
+    pub scope: AggregateScope,
+    pub metrics: Vec<AggregateMetric>,
+}
+
+impl Default for AggregateView {
+    fn default() -> Self {
+        // SSE is on a different scale from the others, leave it to be switched on
+        AggregateView {
+            scope: AggregateScope::All,
+            metrics: vec![AggregateMetric::Rmse, AggregateMetric::MeanAbs, AggregateMetric::MaxAbs],
+        }
+    }
+}
+
+/// Aggregates of one run's errors, lined up with its iterations. NaN where none of the variables
+/// has an error sample for the iteration.
+#[derive(Debug, Default)]
+pub struct ErrorAggregate {
+    pub rmse: Vec<f64>,
+    pub mean_abs: Vec<f64>,
+    pub max_abs: Vec<f64>,
+    pub sse: Vec<f64>,
+}
+
+impl ErrorAggregate {
+    pub fn new(run: &Run, vars: &[usize]) -> Self {
+        let len = run.data.len();
+        let mut sse = vec![0.0; len];
+        let mut sum_abs = vec![0.0; len];
+        let mut max_abs = vec![f64::NAN; len];
+        let mut counts = vec![0usize; len];
+
+        for errors in vars.iter().filter_map(|&var| run.error_series(var)) {
+            for (row, &error) in errors.iter().enumerate().filter(|(_, error)| !error.is_nan()) {
+                sse[row] += error * error;
+                sum_abs[row] += error.abs();
+                max_abs[row] = max_abs[row].max(error.abs()); // max ignores the NaN start
+                counts[row] += 1;
+            }
+        }
+
+        let mut aggregate = ErrorAggregate { max_abs, ..Default::default() };
+        for (row, &count) in counts.iter().enumerate() {
+            if count == 0 {
+                aggregate.rmse.push(f64::NAN);
+                aggregate.mean_abs.push(f64::NAN);
+                aggregate.sse.push(f64::NAN);
+            } else {
+                aggregate.rmse.push((sse[row] / count as f64).sqrt());
+                aggregate.mean_abs.push(sum_abs[row] / count as f64);
+                aggregate.sse.push(sse[row]);
+            }
+        }
+        aggregate
+    }
+
+    pub fn series(&self, metric: AggregateMetric) -> &[f64] {
+        match metric {
+            AggregateMetric::Rmse => &self.rmse,
+            AggregateMetric::MeanAbs => &self.mean_abs,
+            AggregateMetric::MaxAbs => &self.max_abs,
+            AggregateMetric::Sse => &self.sse,
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Y axis scales for the Error plots. Errors often shrink by several orders of magnitude during a
+// calibration, so besides a linear axis they can be drawn on a log or a symmetric log axis. The
+// interactive plot and the exported image both draw transformed samples and label the ticks with
+// the original values.
+
+use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, Ranged, ValueFormatter};
+use plotters::coord::types::RangedCoordf64;
+use std::f64::consts::LN_10;
+use std::ops::Range;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum YScale {
+    #[default]
+    Linear,
+    Log, // Non-positive samples are left out
+    SymLog, // Linear around zero and logarithmic beyond, keeps the sign of the errors
+}
+
+impl YScale {
+    pub const ALL: [YScale; 3] = [YScale::Linear, YScale::Log, YScale::SymLog];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            YScale::Linear => "Linear",
+            YScale::Log => "Log",
+            YScale::SymLog => "Symlog",
+        }
+    }
+}
+
+/// A Y axis scale, with the width of the linear region around zero used by symlog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct YAxis {
+    pub scale: YScale,
+    pub linear_width: f64,
+}
+
+impl Default for YAxis {
+    fn default() -> Self {
+        YAxis { scale: YScale::Linear, linear_width: 1e-6 }
+    }
+}
+
+/// A tick on a transformed axis
+#[derive(Debug, Clone, Copy)]
+pub struct Tick {
+    pub coord: f64, // Position on the transformed axis
+    pub step: f64, // Distance to the next tick of the same weight, in transformed units
+    pub major: bool,
+}
+
+impl YAxis {
+    pub fn is_linear(&self) -> bool {
+        self.scale == YScale::Linear
+    }
+
+    /// Plot coordinate of `value`. Values a log axis cannot show become NaN, which breaks the line
+    /// like a missing sample.
+    pub fn transform(&self, value: f64) -> f64 {
+        match self.scale {
+            YScale::Linear => value,
+            YScale::Log if value > 0.0 => value.log10(),
+            YScale::Log => f64::NAN,
+            YScale::SymLog => value.signum() * (value.abs() / self.linear_width).ln_1p() / LN_10,
+        }
+    }
+
+    /// Value at plot coordinate `coord`
+    pub fn inverse(&self, coord: f64) -> f64 {
+        match self.scale {
+            YScale::Linear => coord,
+            YScale::Log => 10f64.powf(coord),
+            YScale::SymLog => coord.signum() * self.linear_width * (coord.abs() * LN_10).exp_m1(),
+        }
+    }
+
+    /// Ticks between the plot coordinates `min` and `max`: powers of ten, thinned out when the
+    /// range spans many decades. A range too narrow to hold two of them is split evenly instead.
+    pub fn ticks(&self, min: f64, max: f64) -> Vec<Tick> {
+        let mut ticks = Vec::new();
+        if !(min.is_finite() && max.is_finite() && min < max) {
+            return ticks;
+        }
+
+        match self.scale {
+            YScale::Linear => {}
+            YScale::Log => {
+                let (decades, step) = tick_decades(min.floor(), max.ceil());
+                let minor = step == 1 && decades.len() <= 7;
+                for decade in decades {
+                    ticks.push(Tick { coord: decade as f64, step: step as f64, major: true });
+                    if minor {
+                        for factor in 2..10 {
+                            let value = factor as f64 * 10f64.powi(decade);
+                            ticks.push(Tick { coord: value.log10(), step: 0.1, major: false });
+                        }
+                    }
+                }
+            }
+            YScale::SymLog => {
+                ticks.push(Tick { coord: 0.0, step: 1.0, major: true });
+                let largest = self.inverse(min.abs().max(max.abs()));
+                let (decades, step) = tick_decades(self.linear_width.log10().ceil(), largest.log10().ceil());
+                for decade in decades {
+                    let value = 10f64.powi(decade);
+                    for value in [value, -value] {
+                        ticks.push(Tick { coord: self.transform(value), step: step as f64, major: true });
+                    }
+                }
+            }
+        }
+        ticks.retain(|tick| (min..=max).contains(&tick.coord));
+
+        if ticks.iter().filter(|tick| tick.major).count() < 2 {
+            let step = (max - min) / 4.0;
+            ticks = (0..=4)
+                .map(|i| min + step * i as f64)
+                .map(|coord| Tick { coord, step, major: true })
+                .collect();
+        }
+        ticks
+    }
+}
+
+/// The decades from `first` to `last` that get a tick, every how many decades there is one so that
+/// there are about eight, and that step. Clamped to the decades an f64 can hold, so a plot zoomed
+/// far out cannot turn this into billions of iterations.
+fn tick_decades(first: f64, last: f64) -> (Vec<i32>, i32) {
+    let clamp = |decade: f64| decade.clamp(-MAX_DECADE, MAX_DECADE) as i32;
+    let (first, last) = (clamp(first), clamp(last));
+    let step = ((last - first) as f64 / 8.0).ceil().max(1.0) as i32;
+    // Ticks sit on multiples of the step, so they do not shift while panning
+    let start = first.div_euclid(step) * step;
+    let start = if start < first { start + step } else { start };
+    let decades = (start..=last).step_by(step as usize).collect();
+    (decades, step)
+}
+
+/// Largest decade of a tick, beyond the range of f64 either way
+const MAX_DECADE: f64 = 330.0;
+
+/// Short tick label, e.g. "0.05", "250" or "1e-5"
+pub fn format_tick(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude == 0.0 {
+        "0".to_string()
+    } else if (1e-3..1e4).contains(&magnitude) {
+        let text = format!("{value:.4}");
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        let text = format!("{value:.2e}");
+        match text.split_once('e') {
+            Some((mantissa, exponent)) => format!("{}e{exponent}", mantissa.trim_end_matches('0').trim_end_matches('.')),
+            None => text,
+        }
+    }
+}
+
+/// Plotters Y coordinate of an exported plot, placing grid lines and labels at `axis`'s ticks
+pub struct ScaledRange {
+    coords: RangedCoordf64,
+    axis: YAxis,
+}
+
+impl ScaledRange {
+    pub fn new(range: Range<f64>, axis: YAxis) -> Self {
+        ScaledRange { coords: range.into(), axis }
+    }
+}
+
+impl Ranged for ScaledRange {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        self.coords.map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        if self.axis.is_linear() {
+            return self.coords.key_points(hint);
+        }
+        let range = self.coords.range();
+        let light = hint.weight().allow_light_points();
+        self.axis
+            .ticks(range.start, range.end)
+            .into_iter()
+            .filter(|tick| tick.major || light)
+            .map(|tick| tick.coord)
+            .collect()
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.coords.range()
+    }
+}
+
+impl ValueFormatter<f64> for ScaledRange {
+    fn format_ext(&self, value: &f64) -> String {
+        if self.axis.is_linear() {
+            RangedCoordf64::format(value)
+        } else {
+            format_tick(self.axis.inverse(*value))
+        }
+    }
+}
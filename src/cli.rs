@@ -0,0 +1,257 @@
+// Headless rendering of calibration reports, used for batch regeneration of figures
+// without starting the GUI.
+
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::axis::{YAxis, YScale};
+use crate::delta::DeltaPlots;
+use crate::dialect::{DELIMITERS, ReadOptions, TextEncoding};
+use crate::error_mode::ErrorMode;
+use crate::fan::PlotMode;
+use crate::query::Query;
+use crate::forecast::{DecayModel, ForecastSettings};
+use crate::schema::{ColumnSchema, MatchOn, SeriesRule};
+use crate::smoothing::{Smoothing, SmoothingKind};
+use crate::{CalibrationApp, PLOT_COLORS, file_stem};
+
+const RENDER_USAGE: &str = "\
+Usage: visualize_calibration_report render <report.csv>... [options]
+
+Several reports are overlaid on the same plots as separate runs.
+
+Options:
+    --vars <filter>   Variables to plot, as a filter query like in the filter box, e.g.
+                      'Auto* -Transit' or 'converged = false' (default: all)
+    --kind <rule>     Extra series kind, as <name>=<prefix|suffix|regex>:<pattern>,
+                      e.g. Target=prefix:Target: (may be repeated)
+    --delimiter <d>   Field delimiter: comma, semicolon, tab, pipe (default: detected)
+    --encoding <e>    utf-8, utf-16le, utf-16be or windows-1252 (default: detected)
+    --decimal-comma   Numbers are written with a decimal comma (default: detected)
+    --error-mode <m>  Error display: signed, absolute, squared or relative (default: signed)
+    --y-scale <s>     Error axis scale: linear, log or symlog (default: linear)
+    --smooth <rule>   Smooth a plot, as <kind>=ma:<window> or <kind>=ewma:<alpha>,
+                      e.g. Value=ma:10 (may be repeated)
+    --forecast <m>    Project an exponential or power decay fitted to each variable's
+                      recent errors on the Error plot: exponential or power
+    --delta           Also render the steps of Value and Error between iterations, as
+                      delta_value_plot.png and delta_error_plot.png
+    --fan             Draw the median of the variables per iteration inside their 10–90%
+                      and min–max bands instead of a line per variable
+    --out <dir>       Directory to write <kind>_plot.png into (default: .)
+    --dark            Render with the dark theme
+    -h, --help        Print this message";
+
+struct RenderOptions {
+    reports: Vec<String>,
+    vars: String,
+    schema: ColumnSchema,
+    read_options: ReadOptions,
+    error_mode: ErrorMode,
+    error_axis: YAxis,
+    smoothing: HashMap<String, Smoothing>,
+    delta_plots: DeltaPlots,
+    forecast: ForecastSettings,
+    plot_mode: PlotMode,
+    out_dir: PathBuf,
+    dark: bool,
+}
+
+fn parse_render_args(args: &[String]) -> Result<Option<RenderOptions>> {
+    let mut reports = Vec::new();
+    let mut vars = String::new();
+    let mut schema = ColumnSchema::default();
+    let mut read_options = ReadOptions::default();
+    let mut error_mode = ErrorMode::default();
+    let mut error_axis = YAxis::default();
+    let mut smoothing = HashMap::new();
+    let mut delta_plots = DeltaPlots { value: false, error: false };
+    let mut forecast = ForecastSettings { enabled: false, ..Default::default() };
+    let mut plot_mode = PlotMode::Lines;
+    let mut out_dir = PathBuf::from(".");
+    let mut dark = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--vars" => {
+                vars = iter.next().context("--vars requires a filter")?.clone();
+            }
+            "--kind" => {
+                let rule = iter.next().context("--kind requires a rule")?;
+                schema.rules.push(parse_kind_rule(rule)?);
+            }
+            "--delimiter" => {
+                let name = iter.next().context("--delimiter requires a delimiter")?;
+                let Some(&(delimiter, _)) = DELIMITERS.iter().find(|(_, label)| label.eq_ignore_ascii_case(name)) else {
+                    bail!("Unknown delimiter \"{name}\", expected comma, semicolon, tab or pipe");
+                };
+                read_options.delimiter = Some(delimiter);
+            }
+            "--encoding" => {
+                let name = iter.next().context("--encoding requires an encoding")?;
+                // Accept "utf-16le" for the "UTF-16 LE" label
+                let normalize = |label: &str| label.replace(' ', "").to_lowercase();
+                let Some(encoding) = TextEncoding::ALL.into_iter().find(|e| normalize(e.label()) == normalize(name)) else {
+                    bail!("Unknown encoding \"{name}\", expected utf-8, utf-16le, utf-16be or windows-1252");
+                };
+                read_options.encoding = Some(encoding);
+            }
+            "--decimal-comma" => read_options.decimal_comma = Some(true),
+            "--error-mode" => {
+                let name = iter.next().context("--error-mode requires a mode")?;
+                let Some(mode) = ErrorMode::ALL.into_iter().find(|mode| mode.label().eq_ignore_ascii_case(name)) else {
+                    bail!("Unknown error mode \"{name}\", expected signed, absolute, squared or relative");
+                };
+                error_mode = mode;
+            }
+            "--y-scale" => {
+                let name = iter.next().context("--y-scale requires a scale")?;
+                let Some(scale) = YScale::ALL.into_iter().find(|scale| scale.label().eq_ignore_ascii_case(name)) else {
+                    bail!("Unknown scale \"{name}\", expected linear, log or symlog");
+                };
+                error_axis.scale = scale;
+            }
+            "--smooth" => {
+                let rule = iter.next().context("--smooth requires a rule")?;
+                let (kind, plot_smoothing) = parse_smoothing_rule(rule)?;
+                smoothing.insert(kind, plot_smoothing);
+            }
+            "--out" => {
+                out_dir = PathBuf::from(iter.next().context("--out requires a directory")?);
+            }
+            "--forecast" => {
+                let name = iter.next().context("--forecast requires a model")?;
+                forecast.model = match name.to_ascii_lowercase().as_str() {
+                    "exponential" => DecayModel::Exponential,
+                    "power" => DecayModel::PowerLaw,
+                    _ => bail!("Unknown model \"{name}\", expected exponential or power"),
+                };
+                forecast.enabled = true;
+            }
+            "--fan" => plot_mode = PlotMode::Fan,
+            "--delta" => delta_plots = DeltaPlots { value: true, error: true },
+            "--dark" => dark = true,
+            other if other.starts_with('-') => bail!("Unknown option: {other}\n\n{RENDER_USAGE}"),
+            other => reports.push(other.to_string()),
+        }
+    }
+
+    if reports.is_empty() {
+        bail!("Missing report file\n\n{RENDER_USAGE}");
+    }
+    Ok(Some(RenderOptions { reports, vars, schema, read_options, error_mode, error_axis, smoothing, delta_plots, forecast, plot_mode, out_dir, dark }))
+}
+
+/// Parse a `--kind` rule such as `Target=prefix:Target:`
+fn parse_kind_rule(rule: &str) -> Result<SeriesRule> {
+    let parsed = rule.split_once('=').and_then(|(kind, matcher)| {
+        let (match_on, pattern) = matcher.split_once(':')?;
+        let match_on = MatchOn::ALL.into_iter().find(|m| m.label().eq_ignore_ascii_case(match_on))?;
+        Some(SeriesRule::new(kind, match_on, pattern))
+    });
+    let Some(rule) = parsed else {
+        bail!("Invalid --kind rule \"{rule}\", expected <name>=<prefix|suffix|regex>:<pattern>");
+    };
+    if let Some(error) = rule.error() {
+        bail!("Invalid --kind rule: {error}");
+    }
+    Ok(rule)
+}
+
+/// Parse a `--smooth` rule such as `Value=ma:10` or `Error=ewma:0.2`
+fn parse_smoothing_rule(rule: &str) -> Result<(String, Smoothing)> {
+    let parsed = rule.split_once('=').and_then(|(kind, smoothing)| {
+        let (method, param) = smoothing.split_once(':')?;
+        let smoothing = match method.to_ascii_lowercase().as_str() {
+            "ma" => {
+                let window = param.parse().ok().filter(|&window| window > 0)?;
+                Smoothing { kind: SmoothingKind::MovingAverage, window, ..Default::default() }
+            }
+            "ewma" => {
+                let alpha = param.parse().ok().filter(|&alpha| alpha > 0.0 && alpha <= 1.0)?;
+                Smoothing { kind: SmoothingKind::Ewma, alpha, ..Default::default() }
+            }
+            _ => return None,
+        };
+        Some((kind.to_string(), smoothing))
+    });
+    parsed.with_context(|| format!("Invalid --smooth rule \"{rule}\", expected <kind>=ma:<window> or <kind>=ewma:<alpha in (0, 1]>"))
+}
+
+/// Release builds on Windows are GUI programs without a console of their own. Attach to the
+/// console of the shell that started `render` so its output and errors are seen.
+#[cfg(all(not(debug_assertions), target_os = "windows"))]
+pub fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Fails when there is no parent console, e.g. when started from Explorer, which is harmless
+    // SAFETY: AttachConsole takes no pointers and only changes the process' console
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Other builds always have a console
+#[cfg(not(all(not(debug_assertions), target_os = "windows")))]
+pub fn attach_console() {}
+
+/// Entry point for `visualize_calibration_report render ...`
+pub fn run_render(args: &[String]) -> Result<()> {
+    let Some(options) = parse_render_args(args)? else {
+        println!("{RENDER_USAGE}");
+        return Ok(());
+    };
+
+    let filter_query = Query::parse(&options.vars).context("Invalid --vars filter")?;
+    let mut app = CalibrationApp {
+        column_schema: options.schema.clone(),
+        read_options: options.read_options,
+        error_mode: options.error_mode,
+        error_axis: options.error_axis,
+        smoothing: options.smoothing,
+        delta_plots: options.delta_plots,
+        forecast: options.forecast,
+        plot_mode: options.plot_mode,
+        filter_query,
+        ..Default::default()
+    };
+    for report in &options.reports {
+        app.load_file(report.clone())?;
+    }
+    for run in app.runs.iter().filter(|run| !run.data.skipped_cells.is_empty()) {
+        let total_skipped: usize = run.data.skipped_cells.values().sum();
+        println!("Skipped {total_skipped} missing or non-numeric cells in {} columns of {}", run.data.skipped_cells.len(), run.path);
+    }
+
+    let selected_variables: Vec<(usize, &String)> = app.filter_variables()
+        .into_iter()
+        .map(|var_idx| (var_idx, &app.variable_names[var_idx]))
+        .collect();
+    if selected_variables.is_empty() {
+        bail!("No variables match \"{}\"", options.vars);
+    }
+
+    std::fs::create_dir_all(&options.out_dir)
+        .with_context(|| format!("Failed to create output directory: {}", options.out_dir.display()))?;
+
+    for plot_type in app.plot_kinds() {
+        let plot_type = plot_type.as_str();
+        if app.plot_series(&selected_variables, plot_type, &PLOT_COLORS).is_empty() {
+            println!("No {plot_type} columns for the selected variables, skipping");
+            continue;
+        }
+
+        let path = options.out_dir.join(format!("{}_plot.png", file_stem(plot_type)));
+        app.render_plot_image(&path, &selected_variables, plot_type, &PLOT_COLORS, None, options.dark)
+            .with_context(|| format!("Failed to render {}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+
+    Ok(())
+}
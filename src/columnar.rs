@@ -0,0 +1,315 @@
+// Parquet and Arrow IPC reports. Their columns are named as in the CSV reports (Iteration,
+// Error:*, Value:* or the long format), so record batches go through the same row parser, reading
+// numeric columns as numbers rather than text.
+
+use anyhow::{Context, Result, bail};
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, StringArray, UInt32Array};
+use arrow_cast::cast;
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use csv::StringRecord;
+use parquet::arrow::ArrowWriter;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::Compression as ParquetCompression;
+use parquet::file::properties::WriterProperties;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use crate::compression::Compression;
+use crate::data::{CalibrationData, LoadProgress, LoadedReport, RowCells, parse_number};
+use crate::dialect::Dialect;
+use crate::schema::ColumnSchema;
+
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+const ARROW_STREAM_MARKER: &[u8] = &[0xFF, 0xFF, 0xFF, 0xFF]; // Continuation marker of the first message
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Parquet,
+    ArrowFile, // Arrow IPC file, also written as .feather
+    ArrowStream,
+}
+
+impl ReportFormat {
+    /// Work out the format of `file` from its first bytes, leaving the cursor at the start.
+    /// A .parquet or .arrow file without the matching magic bytes is reported as damaged.
+    pub fn detect(path: &str, file: &mut File) -> Result<Self> {
+        let mut magic = Vec::new();
+        file.by_ref().take(ARROW_FILE_MAGIC.len() as u64).read_to_end(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let format = if magic.starts_with(PARQUET_MAGIC) {
+            ReportFormat::Parquet
+        } else if magic.starts_with(ARROW_FILE_MAGIC) {
+            ReportFormat::ArrowFile
+        } else if magic.starts_with(ARROW_STREAM_MARKER) {
+            ReportFormat::ArrowStream
+        } else {
+            ReportFormat::Csv
+        };
+
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let expected = match extension.to_ascii_lowercase().as_str() {
+            "parquet" => ReportFormat::Parquet,
+            "arrow" | "feather" | "ipc" => ReportFormat::ArrowFile,
+            "arrows" => ReportFormat::ArrowStream,
+            _ => ReportFormat::Csv,
+        };
+        if expected != ReportFormat::Csv && format != expected {
+            bail!("{path} is not a valid {} file", expected.label());
+        }
+        Ok(format)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "CSV",
+            ReportFormat::Parquet => "Parquet",
+            ReportFormat::ArrowFile => "Arrow IPC",
+            ReportFormat::ArrowStream => "Arrow IPC stream",
+        }
+    }
+}
+
+/// Load a Parquet or Arrow IPC report. There is nothing to follow in these files, so the whole
+/// file counts as complete.
+pub fn load_columnar(path: &str, file: File, format: ReportFormat, schema: &ColumnSchema, progress: &LoadProgress) -> Result<LoadedReport> {
+    let matcher = schema.compile()?;
+    let file_len = file.metadata()?.len();
+    progress.total_bytes.store(file_len, Ordering::Relaxed);
+
+    type Batches = Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>>;
+    let (arrow_schema, batches, total_rows): (_, Batches, Option<u64>) = match format {
+        ReportFormat::Parquet => {
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file)
+                .with_context(|| format!("Failed to read Parquet metadata: {path}"))?;
+            let total_rows = builder.metadata().file_metadata().num_rows() as u64;
+            (Arc::clone(builder.schema()), Box::new(builder.build()?), Some(total_rows))
+        }
+        ReportFormat::ArrowFile => {
+            let reader = FileReader::try_new(BufReader::new(file), None)
+                .with_context(|| format!("Failed to read Arrow IPC file: {path}"))?;
+            (reader.schema(), Box::new(reader), None)
+        }
+        ReportFormat::ArrowStream => {
+            let reader = StreamReader::try_new(BufReader::new(file), None)
+                .with_context(|| format!("Failed to read Arrow IPC stream: {path}"))?;
+            (reader.schema(), Box::new(reader), None)
+        }
+        ReportFormat::Csv => bail!("{path} is not a Parquet or Arrow file"),
+    };
+
+    let headers: StringRecord = arrow_schema.fields().iter().map(|field| field.name().as_str()).collect();
+    println!("Number of Columns {}", headers.len());
+    let mut data = CalibrationData::from_headers(&headers, &matcher)?;
+
+    let mut rows_read: u64 = 0;
+    let mut kept: usize = 0;
+    for batch in batches {
+        let batch = batch.with_context(|| format!("Failed to read record batch after row {rows_read}"))?;
+        let columns: Vec<BatchColumn> = batch.columns().iter().map(BatchColumn::new).collect();
+
+        for row in 0..batch.num_rows() {
+            if data.push_row(&BatchRow { columns: &columns, row }) {
+                kept += 1;
+            }
+        }
+
+        rows_read += batch.num_rows() as u64;
+        if progress.cancelled.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("Loading cancelled"));
+        }
+        progress.rows_parsed.store(kept, Ordering::Relaxed);
+        if let Some(total_rows) = total_rows.filter(|&total_rows| total_rows > 0) {
+            progress.bytes_read.store(file_len * rows_read / total_rows, Ordering::Relaxed);
+        }
+    }
+    progress.bytes_read.store(file_len, Ordering::Relaxed);
+
+    println!("Finished loading {} records", data.len());
+
+    if data.is_empty() {
+        return Err(anyhow::anyhow!("No records found in file"));
+    }
+
+    Ok(LoadedReport {
+        path: path.to_string(),
+        data,
+        schema: schema.clone(),
+        dialect: Dialect::default(),
+        compression: Compression::None,
+        format,
+        complete_len: file_len,
+        file_len,
+        next_line: rows_read as usize + 2,
+        pending_row: false,
+    })
+}
+
+/// A column of a record batch. Integer, float and decimal columns are read as numbers, so an
+/// Iteration column of floats works as well as one of integers.
+enum BatchColumn {
+    Number(Float64Array),
+    Text(StringArray),
+    Unreadable, // Cannot be shown as text (e.g. nested lists), left empty
+}
+
+impl BatchColumn {
+    fn new(column: &ArrayRef) -> Self {
+        let target = if column.data_type().is_numeric() { DataType::Float64 } else { DataType::Utf8 };
+        match cast(column, &target) {
+            Ok(array) if target == DataType::Float64 => BatchColumn::Number(array.as_primitive().clone()),
+            Ok(array) => BatchColumn::Text(array.as_string::<i32>().clone()),
+            Err(_) => BatchColumn::Unreadable,
+        }
+    }
+}
+
+/// One row of a record batch
+struct BatchRow<'a> {
+    columns: &'a [BatchColumn],
+    row: usize,
+}
+
+impl BatchRow<'_> {
+    fn float(&self, col: usize) -> Option<f64> {
+        match self.columns.get(col)? {
+            BatchColumn::Number(array) if array.is_valid(self.row) => Some(array.value(self.row)),
+            _ => None,
+        }
+    }
+}
+
+impl RowCells for BatchRow<'_> {
+    fn text(&self, col: usize) -> Cow<'_, str> {
+        match self.columns.get(col) {
+            Some(BatchColumn::Text(array)) if array.is_valid(self.row) => Cow::Borrowed(array.value(self.row).trim()),
+            Some(BatchColumn::Number(array)) if array.is_valid(self.row) => Cow::Owned(array.value(self.row).to_string()),
+            _ => Cow::Borrowed(""),
+        }
+    }
+
+    fn number(&self, col: usize, decimal_comma: bool) -> Option<f64> {
+        match self.columns.get(col)? {
+            BatchColumn::Number(_) => self.float(col).filter(|val| val.is_finite()),
+            BatchColumn::Text(_) | BatchColumn::Unreadable => parse_number(&self.text(col), decimal_comma),
+        }
+    }
+
+    fn iteration(&self, col: usize) -> Option<u32> {
+        match self.columns.get(col)? {
+            BatchColumn::Number(_) => self
+                .float(col)
+                .filter(|val| val.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(val))
+                .map(|val| val as u32),
+            BatchColumn::Text(_) | BatchColumn::Unreadable => self.text(col).parse().ok(),
+        }
+    }
+}
+
+/// Write `columns` lined up with `iterations` as a Parquet file, with missing samples as nulls
+pub fn write_parquet(path: &Path, iterations: &[u32], columns: &[(String, Vec<Option<f64>>)]) -> Result<()> {
+    let mut fields = vec![Field::new("Iteration", DataType::UInt32, false)];
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(UInt32Array::from(iterations.to_vec()))];
+    for (name, samples) in columns {
+        fields.push(Field::new(name, DataType::Float64, true));
+        arrays.push(Arc::new(Float64Array::from(samples.clone())));
+    }
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+
+    let file = File::create(path)
+        .with_context(|| format!("Failed to create file: {}", path.display()))?;
+    let properties = WriterProperties::builder().set_compression(ParquetCompression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Float32Array, Int32Array};
+    use std::path::PathBuf;
+
+    /// A file in the temp directory, removed when the test ends
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(std::env::temp_dir().join(format!("{}_{name}", std::process::id())))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn load(path: &Path) -> LoadedReport {
+        let file = File::open(path).unwrap();
+        load_columnar(path.to_str().unwrap(), file, ReportFormat::Parquet, &ColumnSchema::default(), &LoadProgress::default()).unwrap()
+    }
+
+    fn same_samples(actual: &[f64], expected: &[f64]) -> bool {
+        actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| a == e || (a.is_nan() && e.is_nan()))
+    }
+
+    #[test]
+    fn exported_parquet_loads_back() {
+        let file = TempFile::new("exported.parquet");
+        let columns = vec![
+            ("Error:A".to_string(), vec![Some(0.5), None, Some(-0.25)]),
+            ("Value:A".to_string(), vec![Some(10.0), Some(11.0), Some(12.5)]),
+            ("Value:B".to_string(), vec![Some(1.0), Some(2.0), None]),
+        ];
+        write_parquet(&file.0, &[3, 4, 5], &columns).unwrap();
+
+        let report = load(&file.0);
+        let data = &report.data;
+        assert_eq!(data.iterations, [3, 4, 5]);
+        assert_eq!(data.variable_names, ["A", "B"]);
+        assert!(same_samples(data.error_series(0).unwrap(), &[0.5, f64::NAN, -0.25]));
+        assert!(same_samples(data.value_series(0).unwrap(), &[10.0, 11.0, 12.5]));
+        assert!(same_samples(data.value_series(1).unwrap(), &[1.0, 2.0, f64::NAN]));
+        assert!(data.error_series(1).is_none());
+        // Nulls are missing samples, counted like empty CSV cells
+        assert_eq!(data.skipped_cells.get("Error:A"), Some(&1));
+    }
+
+    #[test]
+    fn numeric_columns_of_any_type() {
+        let file = TempFile::new("typed.parquet");
+        let schema = Schema::new(vec![
+            Field::new("Iteration", DataType::Float64, false),
+            Field::new("Error:A", DataType::Float32, true),
+            Field::new("Value:A", DataType::Int32, true),
+        ]);
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(Float64Array::from(vec![1.0, 2.0, 2.5, 4.0])),
+            Arc::new(Float32Array::from(vec![Some(0.5), Some(f32::NAN), Some(0.25), None])),
+            Arc::new(Int32Array::from(vec![Some(7), Some(8), Some(9), Some(10)])),
+        ];
+        let batch = RecordBatch::try_new(Arc::new(schema), arrays).unwrap();
+        let mut writer = ArrowWriter::try_new(File::create(&file.0).unwrap(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let report = load(&file.0);
+        let data = &report.data;
+        // A fractional iteration is not an iteration number, its row is dropped
+        assert_eq!(data.iterations, [1, 2, 4]);
+        assert!(same_samples(data.error_series(0).unwrap(), &[0.5, f64::NAN, f64::NAN]));
+        assert!(same_samples(data.value_series(0).unwrap(), &[7.0, 8.0, 10.0]));
+        assert_eq!(data.skipped_cells.get("Iteration"), Some(&1));
+    }
+}
@@ -0,0 +1,155 @@
+// Compressed reports. Archived calibrations are kept as .csv.gz or .csv.zst and are decompressed
+// on the fly while they are parsed.
+
+use anyhow::{Context, Result, bail};
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const ZSTD_SKIPPABLE_MAGIC: [u8; 3] = [0x2A, 0x4D, 0x18]; // After a first byte of 0x50 to 0x5F
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Work out how `file` is compressed from its first bytes, leaving the cursor at the start.
+    /// A .gz or .zst file without the matching magic bytes is reported as damaged rather than
+    /// read as plain text.
+    pub fn detect(path: &str, file: &mut File) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        let mut len = 0;
+        while len < magic.len() {
+            match file.read(&mut magic[len..])? {
+                0 => break,
+                n => len += n,
+            }
+        }
+        file.seek(SeekFrom::Start(0))?;
+
+        let magic = &magic[..len];
+        let compression = if magic.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if magic.starts_with(&ZSTD_MAGIC) || is_zstd_skippable(magic) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        };
+
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let expected = match extension.to_ascii_lowercase().as_str() {
+            "gz" => Compression::Gzip,
+            "zst" => Compression::Zstd,
+            _ => Compression::None,
+        };
+        if expected != Compression::None && compression != expected {
+            bail!("{path} is not a valid {} file", expected.label());
+        }
+        Ok(compression)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Compression::None => "uncompressed",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    /// Wrap `reader` so that it yields the decompressed bytes
+    pub fn decompress<'a, R: Read + 'a>(self, reader: R) -> Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::None => Box::new(reader),
+            Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            // Decodes every frame in turn, appending to a .zst archive or compressing in parallel
+            // writes several
+            Compression::Zstd => Box::new(zstd::Decoder::new(reader).context("Failed to start zstd decoder")?),
+        })
+    }
+}
+
+/// Whether `magic` starts a skippable zstd frame, which parallel compressors write first
+fn is_zstd_skippable(magic: &[u8]) -> bool {
+    matches!(magic, [first, rest @ ..] if first & 0xF0 == 0x50 && rest == ZSTD_SKIPPABLE_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(text: &str) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn read_all(compression: Compression, bytes: &[u8]) -> Result<String> {
+        let mut text = String::new();
+        compression.decompress(bytes)?.read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    /// Compression detected for `bytes` written to a temp file named `name`
+    fn detect(name: &str, bytes: &[u8]) -> Result<Compression> {
+        let path = std::env::temp_dir().join(format!("{}_{name}", std::process::id()));
+        std::fs::write(&path, bytes).unwrap();
+        let compression = Compression::detect(path.to_str().unwrap(), &mut File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        compression
+    }
+
+    #[test]
+    fn reads_every_gzip_member() {
+        let bytes = [gzip("Iteration,Value:A\n1,10\n"), gzip("2,11\n")].concat();
+        assert_eq!(read_all(Compression::Gzip, &bytes).unwrap(), "Iteration,Value:A\n1,10\n2,11\n");
+    }
+
+    #[test]
+    fn reads_every_zstd_frame() {
+        // A skippable frame with four bytes of metadata, as parallel compressors write first
+        let mut bytes = vec![0x50, 0x2A, 0x4D, 0x18, 4, 0, 0, 0, 1, 2, 3, 4];
+        bytes.extend(zstd::encode_all("Iteration,Value:A\n1,10\n".as_bytes(), 0).unwrap());
+        bytes.extend(zstd::encode_all("2,11\n".as_bytes(), 0).unwrap());
+        assert_eq!(read_all(Compression::Zstd, &bytes).unwrap(), "Iteration,Value:A\n1,10\n2,11\n");
+    }
+
+    #[test]
+    fn truncated_zstd_is_an_error() {
+        let bytes = zstd::encode_all("Iteration,Value:A\n1,10\n".repeat(100).as_bytes(), 0).unwrap();
+        assert!(read_all(Compression::Zstd, &bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn detects_by_magic_bytes() {
+        let zstd = zstd::encode_all("1,10\n".as_bytes(), 0).unwrap();
+        assert_eq!(detect("plain.csv", b"Iteration\n1\n").unwrap(), Compression::None);
+        assert_eq!(detect("report.csv", &gzip("1,10\n")).unwrap(), Compression::Gzip);
+        assert_eq!(detect("report.csv.zst", &zstd).unwrap(), Compression::Zstd);
+        assert_eq!(detect("report.zst", &[0x5E, 0x2A, 0x4D, 0x18, 0, 0, 0, 0]).unwrap(), Compression::Zstd);
+        assert_eq!(detect("short.csv", b"1").unwrap(), Compression::None);
+    }
+
+    #[test]
+    fn extension_without_magic_is_damaged() {
+        assert!(detect("report.csv.gz", b"Iteration\n1\n").is_err());
+        assert!(detect("report.CSV.ZST", &gzip("1,10\n")).is_err());
+    }
+
+    #[test]
+    fn skippable_frame_magic() {
+        assert!(is_zstd_skippable(&[0x50, 0x2A, 0x4D, 0x18]));
+        assert!(is_zstd_skippable(&[0x5F, 0x2A, 0x4D, 0x18]));
+        assert!(!is_zstd_skippable(&[0x60, 0x2A, 0x4D, 0x18]));
+        assert!(!is_zstd_skippable(&[0x50, 0x2A, 0x4D]));
+        assert!(!is_zstd_skippable(&ZSTD_MAGIC));
+    }
+}
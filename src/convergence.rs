@@ -0,0 +1,179 @@
+// Convergence analysis of a run: when each variable's error settled below the tolerance and where
+// it ended up. Feeds the summary table beside the variable grid and the order of the grid itself.
+
+use std::cmp::Ordering;
+
+use crate::data::final_sample;
+use crate::run::Run;
+
+/// Columns of the summary table, each of which it can be sorted by
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryColumn {
+    #[default]
+    Variable,
+    ConvergedAt,
+    FinalError,
+    FinalValue,
+    MaxAbsError,
+}
+
+impl SummaryColumn {
+    pub const ALL: [SummaryColumn; 5] = [
+        SummaryColumn::Variable,
+        SummaryColumn::ConvergedAt,
+        SummaryColumn::FinalError,
+        SummaryColumn::FinalValue,
+        SummaryColumn::MaxAbsError,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SummaryColumn::Variable => "Variable",
+            SummaryColumn::ConvergedAt => "Converged At",
+            SummaryColumn::FinalError => "Final Error",
+            SummaryColumn::FinalValue => "Final Value",
+            SummaryColumn::MaxAbsError => "Max |Error|",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SummarySort {
+    pub column: SummaryColumn,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariableConvergence {
+    pub var: usize, // App-wide variable id
+    pub converged_at: Option<u32>, // None if the error is still outside the tolerance at the end
+    pub final_error: Option<f64>,
+    pub final_value: Option<f64>,
+    pub max_abs_error: Option<f64>,
+}
+
+impl VariableConvergence {
+    /// Analyse one variable of `run`, None if the run does not have it
+    pub fn new(run: &Run, var: usize, tolerance: f64) -> Option<Self> {
+        run.var_ids.get(var).copied().flatten()?;
+        let errors = run.error_series(var);
+        Some(VariableConvergence {
+            var,
+            converged_at: errors.and_then(|errors| run.data.converged_at(errors, tolerance)),
+            final_error: errors.and_then(final_sample),
+            final_value: run.value_series(var).and_then(final_sample),
+            max_abs_error: errors.and_then(|errors| {
+                errors.iter().filter(|error| !error.is_nan()).map(|error| error.abs()).reduce(f64::max)
+            }),
+        })
+    }
+
+    /// The metric shown in `column`, with the variable id standing in for its name (ids follow
+    /// name order)
+    fn sort_key(&self, column: SummaryColumn) -> Option<f64> {
+        match column {
+            SummaryColumn::Variable => Some(self.var as f64),
+            SummaryColumn::ConvergedAt => self.converged_at.map(f64::from),
+            SummaryColumn::FinalError => self.final_error,
+            SummaryColumn::FinalValue => self.final_value,
+            SummaryColumn::MaxAbsError => self.max_abs_error,
+        }
+    }
+}
+
+/// Convergence of each of `vars` that `run` has, in the order given by `sort`. Variables without
+/// the metric (e.g. never converged) come last either way.
+pub fn summarize(run: &Run, vars: &[usize], tolerance: f64, sort: SummarySort) -> Vec<VariableConvergence> {
+    let mut rows: Vec<VariableConvergence> = vars
+        .iter()
+        .filter_map(|&var| VariableConvergence::new(run, var, tolerance))
+        .collect();
+
+    rows.sort_by(|a, b| match (a.sort_key(sort.column), b.sort_key(sort.column)) {
+        (Some(a), Some(b)) if sort.descending => b.total_cmp(&a),
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    rows
+}
+
+/// Orders of the variable grid
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GridSortKey {
+    #[default]
+    Name,
+    FinalAbsError,
+    MaxAbsError,
+    ConvergedAt,
+    ValueChange, // Sum of the absolute steps of the Value series
+    Columns, // Only an Error column, then only a Value column, then both
+}
+
+impl GridSortKey {
+    pub const ALL: [GridSortKey; 6] = [
+        GridSortKey::Name,
+        GridSortKey::FinalAbsError,
+        GridSortKey::MaxAbsError,
+        GridSortKey::ConvergedAt,
+        GridSortKey::ValueChange,
+        GridSortKey::Columns,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GridSortKey::Name => "Name",
+            GridSortKey::FinalAbsError => "Final |Error|",
+            GridSortKey::MaxAbsError => "Max |Error|",
+            GridSortKey::ConvergedAt => "Converged At",
+            GridSortKey::ValueChange => "Value Change",
+            GridSortKey::Columns => "Error/Value Only",
+        }
+    }
+
+    /// The key of one variable of `run`, None if the variable has no such metric. The error
+    /// metrics are the ones the summary table shows.
+    fn key(self, run: &Run, var: usize, tolerance: f64) -> Option<f64> {
+        let convergence = || VariableConvergence::new(run, var, tolerance);
+        match self {
+            GridSortKey::Name => Some(var as f64),
+            GridSortKey::FinalAbsError => convergence()?.final_error.map(f64::abs),
+            GridSortKey::MaxAbsError => convergence()?.max_abs_error,
+            GridSortKey::ConvergedAt => convergence()?.converged_at.map(f64::from),
+            GridSortKey::ValueChange => run.value_series(var).map(|values| {
+                let values: Vec<f64> = values.iter().copied().filter(|val| !val.is_nan()).collect();
+                values.windows(2).map(|step| (step[1] - step[0]).abs()).sum()
+            }),
+            GridSortKey::Columns => match (run.error_series(var).is_some(), run.value_series(var).is_some()) {
+                (true, false) => Some(0.0),
+                (false, true) => Some(1.0),
+                (true, true) => Some(2.0),
+                (false, false) => None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GridSort {
+    pub key: GridSortKey,
+    pub descending: bool,
+}
+
+/// Order `vars` by `sort`, with the metrics taken from `run`. Variables without the metric come
+/// last either way and ties stay in name order.
+pub fn sort_variables(run: &Run, vars: &mut Vec<usize>, tolerance: f64, sort: GridSort) {
+    let mut keyed: Vec<(Option<f64>, usize)> = vars.iter().map(|&var| (sort.key.key(run, var, tolerance), var)).collect();
+    keyed.sort_by(|(a, a_var), (b, b_var)| {
+        let order = match (a, b) {
+            (Some(a), Some(b)) if sort.descending => b.total_cmp(a),
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        order.then(a_var.cmp(b_var))
+    });
+    *vars = keyed.into_iter().map(|(_, var)| var).collect();
+}
@@ -0,0 +1,631 @@
+// Column-oriented storage for a loaded calibration report.
+//
+// Variables are identified by their index into `variable_names` (sorted by name). Each variable owns
+// a dense array per series kind (Error, Value and whatever else the column schema recognises)
+// aligned with `iterations`, with missing samples stored as NaN.
+//
+// Reports come either wide (one row per iteration, a column per variable and kind) or long (one row
+// per iteration and variable, a column per kind); long reports are pivoted into the same layout.
+
+use anyhow::{Context, Result};
+use csv::StringRecord;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use crate::columnar::{ReportFormat, load_columnar};
+use crate::compression::Compression;
+use crate::dialect::{Dialect, ReadOptions};
+use crate::schema::{ColumnMatcher, ColumnSchema, ERROR, VALUE};
+
+/// Cells of a report row, read from CSV text or from the typed columns of a Parquet or Arrow file
+pub trait RowCells {
+    /// Trimmed text of cell `col`, empty if the row has no such cell
+    fn text(&self, col: usize) -> Cow<'_, str>;
+
+    /// Number in cell `col`, None if it is missing, non-numeric or not finite
+    fn number(&self, col: usize, decimal_comma: bool) -> Option<f64>;
+
+    /// Iteration number in cell `col`, None unless it is a whole number that fits a u32
+    fn iteration(&self, col: usize) -> Option<u32>;
+}
+
+impl RowCells for StringRecord {
+    fn text(&self, col: usize) -> Cow<'_, str> {
+        Cow::Borrowed(self.get(col).unwrap_or("").trim())
+    }
+
+    fn number(&self, col: usize, decimal_comma: bool) -> Option<f64> {
+        parse_number(self.get(col).unwrap_or("").trim(), decimal_comma)
+    }
+
+    fn iteration(&self, col: usize) -> Option<u32> {
+        self.get(col).and_then(|cell| cell.trim().parse::<u32>().ok())
+    }
+}
+
+/// What a column of the CSV file feeds into
+#[derive(Debug, Clone, Copy)]
+enum ColumnRole {
+    Ignored,
+    Iteration,
+    Series { kind: usize, var: usize },
+    Variable, // Long format: names the variable of the row
+    Kind(usize), // Long format: samples of this kind for the row's variable
+}
+
+/// How the rows of a report map onto variables
+#[derive(Debug, Default)]
+enum Layout {
+    #[default]
+    Wide,
+    Long { iteration_rows: HashMap<u32, usize> }, // Iteration -> index into `iterations`
+}
+
+#[derive(Debug, Default)]
+pub struct CalibrationData {
+    pub iterations: Vec<u32>,
+    pub variable_names: Vec<String>, // Base variable names without the kind prefix/suffix
+    pub kinds: Vec<String>, // Series kinds of the schema the report was loaded with
+    series: Vec<Vec<Option<Vec<f64>>>>, // Indexed by kind, then variable
+    columns: Vec<Vec<Option<String>>>, // Original column header per kind and variable
+    roles: Vec<ColumnRole>, // Indexed by CSV column
+    iteration_column: usize,
+    layout: Layout,
+    decimal_comma: bool, // Numbers are written like "0,05"
+    pub skipped_cells: HashMap<String, usize>, // Per-column count of missing or non-numeric cells
+}
+
+impl CalibrationData {
+    /// Build an empty store laid out for the columns in `headers`
+    pub fn from_headers(headers: &StringRecord, schema: &ColumnMatcher) -> Result<Self> {
+        if !headers.iter().any(|h| h == "Iteration") {
+            return Err(anyhow::anyhow!("No \"Iteration\" column found in file"));
+        }
+
+        let matches: Vec<Option<(usize, String)>> = headers
+            .iter()
+            .map(|h| if h == "Iteration" { None } else { schema.match_header(h) })
+            .collect();
+
+        // Without any wide-format series columns a Variable column means one row per variable
+        if matches.iter().all(Option::is_none) && headers.iter().any(|h| h.trim() == "Variable") {
+            return Self::from_long_headers(headers, schema);
+        }
+
+        // Create unified variable names (base names without the kind prefix/suffix)
+        let mut variable_names: Vec<String> = matches
+            .iter()
+            .flatten()
+            .map(|(_, base_name)| base_name.clone())
+            .collect();
+        variable_names.sort();
+        variable_names.dedup();
+
+        let variable_ids: HashMap<String, usize> = variable_names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id))
+            .collect();
+
+        let kind_count = schema.kinds().len();
+        let mut data = CalibrationData {
+            kinds: schema.kinds().to_vec(),
+            series: vec![vec![None; variable_names.len()]; kind_count],
+            columns: vec![vec![None; variable_names.len()]; kind_count],
+            variable_names,
+            ..Default::default()
+        };
+
+        for (header, matched) in headers.iter().zip(matches) {
+            // Duplicated columns are ignored after their first occurrence
+            let role = if header == "Iteration" {
+                data.iteration_column = data.roles.len();
+                ColumnRole::Iteration
+            } else if let Some((kind, base_name)) = matched
+                && let var = variable_ids[&base_name]
+                && data.series[kind][var].is_none() {
+                data.series[kind][var] = Some(Vec::new());
+                data.columns[kind][var] = Some(header.to_string());
+                ColumnRole::Series { kind, var }
+            } else {
+                ColumnRole::Ignored
+            };
+            data.roles.push(role);
+        }
+
+        Ok(data)
+    }
+
+    /// Build an empty store for a long-format report, whose columns are named after series kinds
+    fn from_long_headers(headers: &StringRecord, schema: &ColumnMatcher) -> Result<Self> {
+        let kind_count = schema.kinds().len();
+        let mut data = CalibrationData {
+            kinds: schema.kinds().to_vec(),
+            series: vec![Vec::new(); kind_count],
+            columns: vec![Vec::new(); kind_count],
+            layout: Layout::Long { iteration_rows: HashMap::new() },
+            ..Default::default()
+        };
+
+        for header in headers {
+            let header = header.trim();
+            let kind = schema.kinds().iter().position(|kind| kind.eq_ignore_ascii_case(header));
+            // Duplicated columns are ignored after their first occurrence
+            let role = if header == "Iteration" {
+                data.iteration_column = data.roles.len();
+                ColumnRole::Iteration
+            } else if header == "Variable" && !data.roles.iter().any(|r| matches!(r, ColumnRole::Variable)) {
+                ColumnRole::Variable
+            } else if let Some(kind) = kind
+                && !data.roles.iter().any(|r| matches!(r, ColumnRole::Kind(k) if *k == kind)) {
+                ColumnRole::Kind(kind)
+            } else {
+                ColumnRole::Ignored
+            };
+            data.roles.push(role);
+        }
+
+        if !data.roles.iter().any(|r| matches!(r, ColumnRole::Kind(_))) {
+            return Err(anyhow::anyhow!(
+                "Long-format report has a \"Variable\" column but no {} column",
+                schema.kinds().join("/"),
+            ));
+        }
+
+        Ok(data)
+    }
+
+    /// Number of iterations (rows) loaded
+    pub fn len(&self) -> usize {
+        self.iterations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iterations.is_empty()
+    }
+
+    /// Samples of one kind of a variable aligned with `iterations`, NaN where missing
+    pub fn series(&self, kind: &str, var: usize) -> Option<&[f64]> {
+        let kind = self.kinds.iter().position(|k| k == kind)?;
+        self.series[kind][var].as_deref()
+    }
+
+    /// Error samples of a variable aligned with `iterations`, NaN where missing
+    pub fn error_series(&self, var: usize) -> Option<&[f64]> {
+        self.series(ERROR, var)
+    }
+
+    /// Value samples of a variable aligned with `iterations`, NaN where missing
+    pub fn value_series(&self, var: usize) -> Option<&[f64]> {
+        self.series(VALUE, var)
+    }
+
+    /// Points of `series` in iteration order, split into separate runs wherever a sample is
+    /// missing so the plotted line breaks at the gap
+    pub fn segments(&self, series: &[f64]) -> Vec<Vec<[f64; 2]>> {
+        let mut segments = Vec::new();
+        let mut current = Vec::new();
+        for (&iteration, &val) in self.iterations.iter().zip(series) {
+            if !val.is_nan() {
+                current.push([iteration as f64, val]);
+            } else if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+
+    /// First iteration after which the absolute error in `errors` stays below `tolerance`, or None
+    /// if the last sample is still outside it. Missing samples neither break nor start convergence.
+    pub fn converged_at(&self, errors: &[f64], tolerance: f64) -> Option<u32> {
+        let mut converged_at = None;
+        for (&iteration, &error) in self.iterations.iter().zip(errors) {
+            if error.is_nan() {
+                continue;
+            }
+            if error.abs() >= tolerance {
+                converged_at = None;
+            } else if converged_at.is_none() {
+                converged_at = Some(iteration);
+            }
+        }
+        converged_at
+    }
+
+    /// Append every remaining row of `rdr`, advancing `line` past each one. Returns the number of
+    /// rows kept, rows without a usable iteration are dropped. Stops with an error if `progress`
+    /// is cancelled.
+    pub fn read_rows<R: Read>(&mut self, rdr: &mut csv::Reader<R>, line: &mut usize, progress: Option<&LoadProgress>) -> Result<usize> {
+        let mut row = StringRecord::new();
+        let mut kept: usize = 0;
+
+        while rdr.read_record(&mut row)
+            .with_context(|| format!("Failed to parse CSV record at line {line}"))? {
+            *line += 1;
+            if self.push_row(&row) {
+                kept += 1;
+            }
+
+            if let Some(progress) = progress {
+                if progress.cancelled.load(Ordering::Relaxed) {
+                    return Err(anyhow::anyhow!("Loading cancelled"));
+                }
+                progress.rows_parsed.store(kept, Ordering::Relaxed);
+            }
+        }
+
+        Ok(kept)
+    }
+
+    /// Append a row, storing empty, non-numeric and non-finite cells (e.g. "NaN", "inf" or "#N/A"
+    /// written by a crashed iteration) as missing and counting them in `skipped_cells`
+    pub fn push_row(&mut self, row: &impl RowCells) -> bool {
+        let iteration = row.iteration(self.iteration_column);
+        let Some(iteration) = iteration else {
+            *self.skipped_cells.entry("Iteration".to_string()).or_default() += 1;
+            return false;
+        };
+        if let Layout::Long { .. } = self.layout {
+            return self.push_long_row(iteration, row);
+        }
+        self.iterations.push(iteration);
+
+        for (i, role) in self.roles.iter().enumerate() {
+            let (series, column) = match *role {
+                ColumnRole::Series { kind, var } => (&mut self.series[kind][var], &self.columns[kind][var]),
+                ColumnRole::Ignored | ColumnRole::Iteration | ColumnRole::Variable | ColumnRole::Kind(_) => continue,
+            };
+            let (Some(series), Some(column)) = (series, column) else { continue };
+
+            match row.number(i, self.decimal_comma) {
+                Some(val) => series.push(val),
+                None => {
+                    series.push(f64::NAN);
+                    *self.skipped_cells.entry(column.clone()).or_default() += 1;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Store the samples of one variable at `iteration` from a long-format row, adding the
+    /// iteration and the variable if they have not been seen yet
+    fn push_long_row(&mut self, iteration: u32, row: &impl RowCells) -> bool {
+        let variable_column = self.roles.iter().position(|r| matches!(r, ColumnRole::Variable));
+        let name = variable_column.map_or(Cow::Borrowed(""), |i| row.text(i));
+        let name = name.as_ref();
+        if name.is_empty() {
+            *self.skipped_cells.entry("Variable".to_string()).or_default() += 1;
+            return false;
+        }
+
+        let Layout::Long { iteration_rows } = &mut self.layout else {
+            return false;
+        };
+        let row_idx = *iteration_rows.entry(iteration).or_insert_with(|| {
+            self.iterations.push(iteration);
+            for series in self.series.iter_mut().flatten().flatten() {
+                series.push(f64::NAN);
+            }
+            self.iterations.len() - 1
+        });
+
+        let var = match self.variable_names.binary_search_by(|v| v.as_str().cmp(name)) {
+            Ok(var) => var,
+            Err(var) => {
+                // Keep the names sorted, later variables shift up by one
+                self.variable_names.insert(var, name.to_string());
+                for (kind, (kind_series, kind_columns)) in self.series.iter_mut().zip(&mut self.columns).enumerate() {
+                    let has_column = self.roles.iter().any(|r| matches!(r, ColumnRole::Kind(k) if *k == kind));
+                    kind_series.insert(var, has_column.then(|| vec![f64::NAN; self.iterations.len()]));
+                    kind_columns.insert(var, has_column.then(|| format!("{}:{name}", self.kinds[kind])));
+                }
+                var
+            }
+        };
+
+        for (i, role) in self.roles.iter().enumerate() {
+            let ColumnRole::Kind(kind) = *role else { continue };
+            let (Some(series), Some(column)) = (&mut self.series[kind][var], &self.columns[kind][var]) else { continue };
+
+            match row.number(i, self.decimal_comma) {
+                Some(val) => series[row_idx] = val,
+                None => *self.skipped_cells.entry(column.clone()).or_default() += 1,
+            }
+        }
+
+        true
+    }
+
+    /// Remove the last row, undoing its contribution to `skipped_cells`
+    pub fn pop_row(&mut self) {
+        if self.iterations.pop().is_none() {
+            return;
+        }
+
+        for (kind_series, kind_columns) in self.series.iter_mut().zip(&self.columns) {
+            for (series, column) in kind_series.iter_mut().zip(kind_columns) {
+                if let Some(series) = series
+                    && let Some(val) = series.pop()
+                    && val.is_nan()
+                    && let Some(column) = column
+                    && let Some(count) = self.skipped_cells.get_mut(column)
+                {
+                    *count -= 1;
+                }
+            }
+        }
+        self.skipped_cells.retain(|_, count| *count > 0);
+    }
+}
+
+/// Parse a numeric cell, None if it is empty, non-numeric or not finite
+pub fn parse_number(cell: &str, decimal_comma: bool) -> Option<f64> {
+    let val = if decimal_comma && cell.contains(',') {
+        cell.replacen(',', ".", 1).parse::<f64>()
+    } else {
+        cell.parse::<f64>()
+    };
+    val.ok().filter(|val| val.is_finite())
+}
+
+/// Last sample of `series` that is not missing
+pub fn final_sample(series: &[f64]) -> Option<f64> {
+    series.iter().rev().copied().find(|val| !val.is_nan())
+}
+
+/// Absolute error a variable has to stay below to count as converged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance(pub f64);
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance(0.01)
+    }
+}
+
+/// Progress of a load, shared between the loading thread and the UI
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    pub total_bytes: AtomicU64,
+    pub bytes_read: AtomicU64,
+    pub rows_parsed: AtomicUsize,
+    pub cancelled: AtomicBool,
+}
+
+/// Counts the bytes pulled through it into `LoadProgress::bytes_read`
+struct ProgressReader<'a, R> {
+    inner: R,
+    progress: &'a LoadProgress,
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.progress.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// A parsed report together with where parsing stopped, so follow mode can pick up from there
+#[derive(Debug)]
+pub struct LoadedReport {
+    pub path: String,
+    pub data: CalibrationData,
+    pub schema: ColumnSchema, // Schema the columns were matched with
+    pub dialect: Dialect, // Delimiter, encoding and decimal separator the file was read with
+    pub compression: Compression,
+    pub format: ReportFormat,
+    pub complete_len: u64, // Bytes up to and including the last line ending
+    pub file_len: u64,
+    pub next_line: usize, // Line number of the row starting at complete_len
+    pub pending_row: bool, // The last row had no line ending yet
+}
+
+/// Read and parse a whole calibration report
+pub fn load_report(path: &str, schema: &ColumnSchema, options: &ReadOptions, progress: &LoadProgress) -> Result<LoadedReport> {
+    println!("Starting to load file: {path}");
+    let matcher = schema.compile()?;
+
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file: {path}"))?;
+    let format = ReportFormat::detect(path, &mut file)?;
+    if format != ReportFormat::Csv {
+        println!("Reading {} file", format.label());
+        return load_columnar(path, file, format, schema, progress);
+    }
+    let compression = Compression::detect(path, &mut file)?;
+    let dialect = Dialect::detect(&read_sample(&mut file, compression)?, options);
+    println!("Reading {} file as {}", compression.label(), dialect.describe());
+    let file_len = file.metadata()?.len();
+    // Compressed reports are finished archives, their last line is never still being written
+    let complete_len = match compression {
+        Compression::None => complete_lines_len(&mut file, &dialect)?,
+        Compression::Gzip | Compression::Zstd => file_len,
+    };
+    progress.total_bytes.store(file_len, Ordering::Relaxed);
+
+    // Progress counts compressed bytes, which is what the file size is measured in
+    let reader = compression.decompress(ProgressReader { inner: file.take(complete_len), progress })?;
+    let mut rdr = dialect.csv_reader(reader, true);
+    let headers = rdr.headers()?.clone();
+    println!("Number of Columns {}", headers.len());
+
+    let mut data = CalibrationData::from_headers(&headers, &matcher)?;
+    data.decimal_comma = dialect.decimal_comma;
+    let mut line = 2;
+    data.read_rows(&mut rdr, &mut line, Some(progress))?;
+
+    // A final line without a line ending may still be in the middle of being written,
+    // only keep it if it is already a whole row
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file: {path}"))?;
+    file.seek(SeekFrom::Start(complete_len))?;
+    let mut unterminated = Vec::new();
+    file.read_to_end(&mut unterminated)?;
+    let pending_row = push_unterminated_row(&mut data, &unterminated, headers.len(), &dialect);
+    progress.bytes_read.store(file_len, Ordering::Relaxed);
+
+    println!("Finished loading {} records", data.len());
+
+    if data.is_empty() {
+        return Err(anyhow::anyhow!("No records found in file"));
+    }
+
+    Ok(LoadedReport {
+        path: path.to_string(),
+        data,
+        schema: schema.clone(),
+        dialect,
+        compression,
+        format,
+        complete_len,
+        file_len: complete_len + unterminated.len() as u64,
+        next_line: line,
+        pending_row,
+    })
+}
+
+/// Parse a trailing line that has no line ending into `data`, if it already holds a complete row
+fn push_unterminated_row(data: &mut CalibrationData, bytes: &[u8], column_count: usize, dialect: &Dialect) -> bool {
+    // A long-format row only fills in part of an iteration and cannot be taken back with pop_row,
+    // wait until it has a line ending
+    if let Layout::Long { .. } = data.layout {
+        return false;
+    }
+    let mut rdr = dialect.csv_reader(bytes, false);
+    let mut row = StringRecord::new();
+    match rdr.read_record(&mut row) {
+        Ok(true) if row.len() == column_count => data.push_row(&row),
+        _ => false,
+    }
+}
+
+/// The first decompressed bytes of the file for format detection, leaving the cursor at the start
+fn read_sample(file: &mut File, compression: Compression) -> Result<Vec<u8>> {
+    const SAMPLE: u64 = 64 * 1024;
+    let mut sample = Vec::new();
+    compression.decompress(file.by_ref())?.take(SAMPLE).read_to_end(&mut sample)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(sample)
+}
+
+/// Length of the file up to and including its last line ending, leaving the cursor at the start
+fn complete_lines_len(file: &mut File, dialect: &Dialect) -> Result<u64> {
+    const CHUNK: u64 = 4096;
+    let file_len = file.seek(SeekFrom::End(0))?;
+    let mut end = file_len;
+    let mut buffer = vec![0u8; CHUNK as usize + 1];
+    let mut complete_len = 0;
+
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK);
+        // One byte past the chunk, so a UTF-16 line ending split across chunks is still found
+        let read_end = (end + 1).min(file_len);
+        let chunk = &mut buffer[..(read_end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(len) = dialect.last_line_end(chunk, start) {
+            complete_len = start + len as u64;
+            break;
+        }
+        end = start;
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    Ok(complete_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse `csv` (header line first) with the default schema
+    fn parse(csv: &str) -> CalibrationData {
+        let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(csv.as_bytes());
+        let headers = rdr.headers().unwrap().clone();
+        let mut data = CalibrationData::from_headers(&headers, &ColumnSchema::default().compile().unwrap()).unwrap();
+        let mut line = 2;
+        data.read_rows(&mut rdr, &mut line, None).unwrap();
+        data
+    }
+
+    fn same_samples(actual: &[f64], expected: &[f64]) -> bool {
+        actual.len() == expected.len() && actual.iter().zip(expected).all(|(a, e)| a == e || (a.is_nan() && e.is_nan()))
+    }
+
+    #[test]
+    fn missing_cells_break_the_line() {
+        let data = parse("Iteration,Error:A,Value:A\n1,0.5,10\n2,,11\n3,#N/A,12\n4,0.25,13\n5,inf\n");
+        assert_eq!(data.iterations, [1, 2, 3, 4, 5]);
+        let errors = data.error_series(0).unwrap();
+        assert!(same_samples(errors, &[0.5, f64::NAN, f64::NAN, 0.25, f64::NAN]));
+        assert_eq!(data.segments(errors), [vec![[1.0, 0.5]], vec![[4.0, 0.25]]]);
+        // The short last row is missing its Value cell
+        assert!(same_samples(data.value_series(0).unwrap(), &[10.0, 11.0, 12.0, 13.0, f64::NAN]));
+        assert_eq!(data.skipped_cells.get("Error:A"), Some(&3));
+        assert_eq!(data.skipped_cells.get("Value:A"), Some(&1));
+    }
+
+    #[test]
+    fn rows_without_an_iteration_are_dropped() {
+        let data = parse("Iteration,Value:A\n1,10\nx,11\n,12\n-1,13\n2,14\n");
+        assert_eq!(data.iterations, [1, 2]);
+        assert!(same_samples(data.value_series(0).unwrap(), &[10.0, 14.0]));
+        assert_eq!(data.skipped_cells.get("Iteration"), Some(&3));
+    }
+
+    #[test]
+    fn pop_row_undoes_skipped_cells() {
+        let mut data = parse("Iteration,Error:A,Value:A\n1,,10\n2,,\n");
+        assert_eq!(data.skipped_cells.get("Error:A"), Some(&2));
+        data.pop_row();
+        assert_eq!(data.iterations, [1]);
+        assert_eq!(data.skipped_cells.get("Error:A"), Some(&1));
+        assert!(!data.skipped_cells.contains_key("Value:A"));
+    }
+
+    #[test]
+    fn decimal_comma_numbers() {
+        assert_eq!(parse_number("0,05", true), Some(0.05));
+        assert_eq!(parse_number("0,05", false), None);
+        assert_eq!(parse_number("1e-3", true), Some(0.001));
+        assert_eq!(parse_number("NaN", false), None);
+    }
+
+    #[test]
+    fn long_format_is_pivoted() {
+        let data = parse("Iteration,Variable,Error,Value\n1,B,0.5,20\n1,A,0.25,10\n2,C,,30\n2,A,0.125,11\n1,C,0.75,\n");
+        assert_eq!(data.iterations, [1, 2]);
+        // Variables are inserted in name order wherever they first appear
+        assert_eq!(data.variable_names, ["A", "B", "C"]);
+        assert!(same_samples(data.error_series(0).unwrap(), &[0.25, 0.125]));
+        assert!(same_samples(data.value_series(1).unwrap(), &[20.0, f64::NAN]));
+        // A later row for an earlier iteration fills in that iteration
+        assert!(same_samples(data.error_series(2).unwrap(), &[0.75, f64::NAN]));
+        assert!(same_samples(data.value_series(2).unwrap(), &[f64::NAN, 30.0]));
+        assert_eq!(data.skipped_cells.get("Error:C"), Some(&1));
+        assert_eq!(data.skipped_cells.get("Value:C"), Some(&1));
+    }
+
+    #[test]
+    fn long_format_needs_a_kind_column() {
+        let headers = StringRecord::from(vec!["Iteration", "Variable", "Residual"]);
+        let matcher = ColumnSchema::default().compile().unwrap();
+        assert!(CalibrationData::from_headers(&headers, &matcher).is_err());
+    }
+
+    #[test]
+    fn long_format_rows_need_a_variable() {
+        let data = parse("Iteration,Variable,Value\n1,,10\n2,A,11\n");
+        assert_eq!(data.iterations, [2]);
+        assert_eq!(data.skipped_cells.get("Variable"), Some(&1));
+    }
+}
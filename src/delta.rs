@@ -0,0 +1,36 @@
+// Step sizes: first differences of a series between consecutive iterations of a report. A Value
+// that has visually settled may still be moving, which shows up clearly in its steps.
+
+/// Prefix of the plot kinds holding the steps of another kind, e.g. "ΔValue"
+pub const DELTA: &str = "Δ";
+
+/// Which step plots are shown under the Error/Value plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeltaPlots {
+    pub value: bool,
+    pub error: bool,
+}
+
+impl Default for DeltaPlots {
+    fn default() -> Self {
+        DeltaPlots { value: true, error: false }
+    }
+}
+
+/// Plot kind of the steps of `kind`
+pub fn delta_kind(kind: &str) -> String {
+    format!("{DELTA}{kind}")
+}
+
+/// The kind a step plot kind differences, None for other kinds
+pub fn base_kind(plot_type: &str) -> Option<&str> {
+    plot_type.strip_prefix(DELTA)
+}
+
+/// Change of each sample from the previous one, missing for the first sample and next to gaps
+pub fn first_difference(samples: &[f64]) -> Vec<f64> {
+    let mut steps = Vec::with_capacity(samples.len());
+    steps.extend(samples.first().map(|_| f64::NAN));
+    steps.extend(samples.windows(2).map(|pair| pair[1] - pair[0])); // NaN propagates through gaps
+    steps
+}
@@ -0,0 +1,300 @@
+// Text format of a report file: field delimiter, text encoding and decimal separator. Each is
+// detected from the start of the file unless chosen by hand in the file section.
+
+use csv::ReaderBuilder;
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+use std::io::Read;
+
+/// Delimiters offered in the file section and tried by detection
+pub const DELIMITERS: [(u8, &str); 4] = [(b',', "Comma"), (b';', "Semicolon"), (b'\t', "Tab"), (b'|', "Pipe")];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252, // Also covers Latin-1 files written by older tools
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 4] = [TextEncoding::Utf8, TextEncoding::Utf16Le, TextEncoding::Utf16Be, TextEncoding::Windows1252];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "UTF-8",
+            TextEncoding::Utf16Le => "UTF-16 LE",
+            TextEncoding::Utf16Be => "UTF-16 BE",
+            TextEncoding::Windows1252 => "Windows-1252",
+        }
+    }
+
+    fn encoding(self) -> &'static Encoding {
+        match self {
+            TextEncoding::Utf8 => UTF_8,
+            TextEncoding::Utf16Le => UTF_16LE,
+            TextEncoding::Utf16Be => UTF_16BE,
+            TextEncoding::Windows1252 => WINDOWS_1252,
+        }
+    }
+
+    /// Guess the encoding from a byte order mark, the position of zero bytes, or whether the
+    /// bytes are valid UTF-8
+    fn detect(sample: &[u8]) -> Self {
+        match sample {
+            [0xEF, 0xBB, 0xBF, ..] => return TextEncoding::Utf8,
+            [0xFF, 0xFE, ..] => return TextEncoding::Utf16Le,
+            [0xFE, 0xFF, ..] => return TextEncoding::Utf16Be,
+            _ => {}
+        }
+
+        // ASCII text in UTF-16 has every other byte zero
+        let zeros_at = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|&&b| b == 0).count();
+        let half = sample.len() / 2;
+        if half > 0 && zeros_at(1) * 2 > half {
+            return TextEncoding::Utf16Le;
+        }
+        if half > 0 && zeros_at(0) * 2 > half {
+            return TextEncoding::Utf16Be;
+        }
+
+        match std::str::from_utf8(sample) {
+            Ok(_) => TextEncoding::Utf8,
+            // The sample may end part way through a character
+            Err(e) if e.error_len().is_none() => TextEncoding::Utf8,
+            Err(_) => TextEncoding::Windows1252,
+        }
+    }
+}
+
+/// Format choices for reading reports, None = detect from the file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    pub delimiter: Option<u8>,
+    pub encoding: Option<TextEncoding>,
+    pub decimal_comma: Option<bool>,
+}
+
+/// The format a report was read with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dialect {
+    pub delimiter: u8,
+    pub encoding: TextEncoding,
+    pub decimal_comma: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Dialect { delimiter: b',', encoding: TextEncoding::Utf8, decimal_comma: false }
+    }
+}
+
+impl Dialect {
+    /// Work out the format of a report from the first bytes of the file, keeping whatever
+    /// `options` fixes
+    pub fn detect(sample: &[u8], options: &ReadOptions) -> Self {
+        let encoding = options.encoding.unwrap_or_else(|| TextEncoding::detect(sample));
+        let (text, _) = encoding.encoding().decode_with_bom_removal(sample);
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or("");
+
+        let delimiter = options.delimiter.unwrap_or_else(|| {
+            // The header names contain no delimiters, so the most frequent candidate separates them
+            DELIMITERS
+                .iter()
+                .map(|&(delimiter, _)| (delimiter, count_unquoted(header, delimiter as char)))
+                .filter(|&(_, count)| count > 0)
+                .max_by_key(|&(_, count)| count)
+                .map_or(b',', |(delimiter, _)| delimiter)
+        });
+
+        let decimal_comma = options.decimal_comma.unwrap_or_else(|| {
+            // A comma can only be a decimal separator if it does not separate fields
+            delimiter != b',' && lines.take(20).any(|line| {
+                line.split(delimiter as char).any(|field| is_decimal_comma_number(field.trim()))
+            })
+        });
+
+        Dialect { delimiter, encoding, decimal_comma }
+    }
+
+    /// Transcode `reader` to UTF-8, dropping a byte order mark at its start
+    pub fn decoder<R: Read>(&self, reader: R) -> DecodeReaderBytes<R, Vec<u8>> {
+        DecodeReaderBytesBuilder::new()
+            .encoding(Some(self.encoding.encoding()))
+            .utf8_passthru(true)
+            .strip_bom(true)
+            .build(reader)
+    }
+
+    pub fn csv_reader<R: Read>(&self, reader: R, has_headers: bool) -> csv::Reader<DecodeReaderBytes<R, Vec<u8>>> {
+        ReaderBuilder::new()
+            .has_headers(has_headers)
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .from_reader(self.decoder(reader))
+    }
+
+    /// Length of `bytes` up to and including its last line ending, where `offset` is the position
+    /// of `bytes` in the file (UTF-16 line endings start at even offsets)
+    pub fn last_line_end(&self, bytes: &[u8], offset: u64) -> Option<usize> {
+        let utf16_line_end = |newline: [u8; 2]| {
+            (0..bytes.len().saturating_sub(1))
+                .rev()
+                .find(|&i| (offset + i as u64).is_multiple_of(2) && bytes[i..i + 2] == newline)
+                .map(|i| i + 2)
+        };
+        match self.encoding {
+            TextEncoding::Utf8 | TextEncoding::Windows1252 => bytes.iter().rposition(|&b| b == b'\n').map(|i| i + 1),
+            TextEncoding::Utf16Le => utf16_line_end([b'\n', 0]),
+            TextEncoding::Utf16Be => utf16_line_end([0, b'\n']),
+        }
+    }
+
+    /// Whether a report read with this format already follows every choice in `options`
+    pub fn satisfies(&self, options: &ReadOptions) -> bool {
+        options.delimiter.is_none_or(|delimiter| delimiter == self.delimiter)
+            && options.encoding.is_none_or(|encoding| encoding == self.encoding)
+            && options.decimal_comma.is_none_or(|decimal_comma| decimal_comma == self.decimal_comma)
+    }
+
+    /// Short summary for the file section, e.g. "Semicolon · UTF-16 LE · decimal comma"
+    pub fn describe(&self) -> String {
+        let delimiter = DELIMITERS
+            .iter()
+            .find(|&&(d, _)| d == self.delimiter)
+            .map_or_else(|| format!("'{}'", self.delimiter as char), |&(_, name)| name.to_string());
+        let decimal = if self.decimal_comma { "decimal comma" } else { "decimal point" };
+        format!("{delimiter} · {} · {decimal}", self.encoding.label())
+    }
+}
+
+/// Number of `delimiter` characters in `line` outside double quotes
+fn count_unquoted(line: &str, delimiter: char) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for c in line.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Whether `field` is a number written with a decimal comma, e.g. "-0,05" or "1,2E-3"
+fn is_decimal_comma_number(field: &str) -> bool {
+    let Some((whole, fraction)) = field.split_once(',') else {
+        return false;
+    };
+    let whole = whole.strip_prefix('-').unwrap_or(whole);
+    !whole.is_empty()
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().next().is_some_and(|b| b.is_ascii_digit())
+        && format!("{whole}.{fraction}").parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(text: &str, little_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() })
+            .collect()
+    }
+
+    fn detect(sample: &[u8]) -> Dialect {
+        Dialect::detect(sample, &ReadOptions::default())
+    }
+
+    #[test]
+    fn detects_delimiters() {
+        assert_eq!(detect(b"Iteration,Error:A,Value:A\n1,0.5,2\n").delimiter, b',');
+        assert_eq!(detect(b"Iteration;Error:A;Value:A\n1;0.5;2\n").delimiter, b';');
+        assert_eq!(detect(b"Iteration\tError:A\tValue:A\n").delimiter, b'\t');
+        assert_eq!(detect(b"Iteration|Error:A|Value:A\n").delimiter, b'|');
+        // Commas inside quoted names do not count
+        assert_eq!(detect(b"Iteration;\"Error:A,B\";\"Value:A,B\"\n").delimiter, b';');
+        // A single column falls back to commas
+        assert_eq!(detect(b"Iteration\n1\n").delimiter, b',');
+    }
+
+    #[test]
+    fn detects_decimal_comma() {
+        assert!(detect(b"Iteration;Error:A\n1;-0,05\n").decimal_comma);
+        assert!(detect(b"Iteration\tError:A\n1\t1,2E-3\n").decimal_comma);
+        assert!(!detect(b"Iteration;Error:A\n1;-0.05\n").decimal_comma);
+        // With commas between fields a comma is never a decimal separator
+        assert!(!detect(b"Iteration,Error:A\n1,5\n").decimal_comma);
+        // Not numbers
+        assert!(!detect(b"Iteration;Note\n1;a,b\n1;,5\n").decimal_comma);
+    }
+
+    #[test]
+    fn detects_utf16_by_zero_byte_parity() {
+        let text = "Iteration;Error:A\n1;0,5\n";
+        let little = detect(&utf16(text, true));
+        assert_eq!(little.encoding, TextEncoding::Utf16Le);
+        assert_eq!(little.delimiter, b';');
+        assert!(little.decimal_comma);
+        assert_eq!(detect(&utf16(text, false)).encoding, TextEncoding::Utf16Be);
+    }
+
+    #[test]
+    fn detects_byte_order_marks() {
+        let mut little = vec![0xFF, 0xFE];
+        little.extend(utf16("Iteration;Error:A\n", true));
+        assert_eq!(detect(&little).encoding, TextEncoding::Utf16Le);
+        assert_eq!(detect(&little).delimiter, b';');
+        let mut big = vec![0xFE, 0xFF];
+        big.extend(utf16("Iteration,Error:A\n", false));
+        assert_eq!(detect(&big).encoding, TextEncoding::Utf16Be);
+        assert_eq!(detect(b"\xEF\xBB\xBFIteration\tError:A\n").encoding, TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn detects_windows_1252() {
+        assert_eq!(detect(b"Iteration,Error:Caf\xE9\n").encoding, TextEncoding::Windows1252);
+        assert_eq!(detect("Iteration,Error:Café\n".as_bytes()).encoding, TextEncoding::Utf8);
+        // A sample cut part way through a character is still UTF-8
+        assert_eq!(detect(&"Iteration,Error:Café".as_bytes()[..20]).encoding, TextEncoding::Utf8);
+    }
+
+    #[test]
+    fn options_override_detection() {
+        let options = ReadOptions { delimiter: Some(b'|'), encoding: Some(TextEncoding::Windows1252), decimal_comma: Some(true) };
+        let dialect = Dialect::detect(b"Iteration,Error:A\n1,0.5\n", &options);
+        assert_eq!(dialect, Dialect { delimiter: b'|', encoding: TextEncoding::Windows1252, decimal_comma: true });
+        assert!(dialect.satisfies(&options));
+        assert!(dialect.satisfies(&ReadOptions::default()));
+        assert!(!Dialect::default().satisfies(&options));
+    }
+
+    #[test]
+    fn last_line_end() {
+        let utf8 = Dialect::default();
+        assert_eq!(utf8.last_line_end(b"1,2\n3,4\n5,", 0), Some(8));
+        assert_eq!(utf8.last_line_end(b"5,6", 0), None);
+
+        // A UTF-16 line ending starts at an even offset in the file
+        let little = Dialect { encoding: TextEncoding::Utf16Le, ..Dialect::default() };
+        let bytes = utf16("1\n2", true);
+        assert_eq!(little.last_line_end(&bytes, 0), Some(4));
+        // The same bytes read from an odd offset are not aligned with the characters
+        assert_eq!(little.last_line_end(&bytes, 1), None);
+        let big = Dialect { encoding: TextEncoding::Utf16Be, ..Dialect::default() };
+        assert_eq!(big.last_line_end(&utf16("1\n2\n3", false), 0), Some(8));
+    }
+
+    #[test]
+    fn decodes_utf16_rows() {
+        let bytes = utf16("Iteration;Error:A\n1;0,5\n", true);
+        let dialect = detect(&bytes);
+        let mut rdr = dialect.csv_reader(&bytes[..], true);
+        let row = rdr.records().next().unwrap().unwrap();
+        assert_eq!(&row[0], "1");
+        assert_eq!(&row[1], "0,5");
+    }
+}
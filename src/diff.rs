@@ -0,0 +1,98 @@
+// Comparison of a candidate run against a baseline run. Variables are matched by base name, which
+// is what the app-wide variable ids already encode.
+
+use std::collections::HashMap;
+
+use crate::data::final_sample;
+use crate::run::Run;
+
+/// A metric of one variable in both runs
+#[derive(Debug, Clone, Copy)]
+pub struct MetricDiff<T> {
+    pub baseline: Option<T>,
+    pub candidate: Option<T>,
+}
+
+impl<T: Copy + Into<f64>> MetricDiff<T> {
+    /// Candidate minus baseline, if both runs have the metric
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.candidate?.into() - self.baseline?.into())
+    }
+}
+
+#[derive(Debug)]
+pub struct VariableDiff {
+    pub var: usize, // App-wide variable id
+    pub final_error: MetricDiff<f64>,
+    pub final_value: MetricDiff<f64>,
+    pub converged_at: MetricDiff<u32>,
+}
+
+impl VariableDiff {
+    /// Whether the candidate ends closer to its target than the baseline
+    pub fn improved(&self) -> Option<bool> {
+        Some(self.final_error.candidate?.abs() < self.final_error.baseline?.abs())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RunDiff {
+    pub variables: Vec<VariableDiff>, // Variables present in both runs
+    pub only_in_baseline: Vec<usize>,
+    pub only_in_candidate: Vec<usize>,
+}
+
+impl RunDiff {
+    pub fn new(variable_count: usize, baseline: &Run, candidate: &Run, tolerance: f64) -> Self {
+        let mut diff = RunDiff::default();
+
+        for var in 0..variable_count {
+            match (baseline.var_ids[var], candidate.var_ids[var]) {
+                (Some(_), Some(_)) => {
+                    let metric = |run: &Run| {
+                        let errors = run.error_series(var);
+                        (
+                            errors.and_then(final_sample),
+                            run.value_series(var).and_then(final_sample),
+                            errors.and_then(|errors| run.data.converged_at(errors, tolerance)),
+                        )
+                    };
+                    let (baseline_error, baseline_value, baseline_converged) = metric(baseline);
+                    let (candidate_error, candidate_value, candidate_converged) = metric(candidate);
+
+                    diff.variables.push(VariableDiff {
+                        var,
+                        final_error: MetricDiff { baseline: baseline_error, candidate: candidate_error },
+                        final_value: MetricDiff { baseline: baseline_value, candidate: candidate_value },
+                        converged_at: MetricDiff { baseline: baseline_converged, candidate: candidate_converged },
+                    });
+                }
+                (Some(_), None) => diff.only_in_baseline.push(var),
+                (None, Some(_)) => diff.only_in_candidate.push(var),
+                (None, None) => {}
+            }
+        }
+
+        diff
+    }
+}
+
+/// Candidate minus baseline error of a variable, aligned with the candidate's iterations and NaN
+/// where either run has no sample for that iteration
+pub fn error_difference(baseline: &Run, candidate: &Run, var: usize) -> Option<Vec<f64>> {
+    let baseline_errors: HashMap<u32, f64> = baseline.data.iterations
+        .iter()
+        .copied()
+        .zip(baseline.error_series(var)?.iter().copied())
+        .collect();
+
+    let difference = candidate.data.iterations
+        .iter()
+        .zip(candidate.error_series(var)?)
+        .map(|(iteration, &error)| match baseline_errors.get(iteration) {
+            Some(&baseline_error) => error - baseline_error, // NaN if either is missing
+            None => f64::NAN,
+        })
+        .collect();
+    Some(difference)
+}
@@ -0,0 +1,77 @@
+// How Error series are shown. Reports hold signed errors, the plots and exports can show them as
+// magnitudes, squares or relative to the variable's target instead.
+
+use std::borrow::Cow;
+
+/// Series kind relative errors are divided by when a run has it, otherwise its Value series is used
+pub const TARGET: &str = "Target";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    #[default]
+    Signed,
+    Absolute,
+    Squared,
+    Relative, // Error divided by the magnitude of the target (or value)
+}
+
+impl ErrorMode {
+    pub const ALL: [ErrorMode; 4] = [ErrorMode::Signed, ErrorMode::Absolute, ErrorMode::Squared, ErrorMode::Relative];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorMode::Signed => "Signed",
+            ErrorMode::Absolute => "Absolute",
+            ErrorMode::Squared => "Squared",
+            ErrorMode::Relative => "Relative",
+        }
+    }
+
+    /// Y axis label of plots in this mode
+    pub fn axis_label(self) -> &'static str {
+        match self {
+            ErrorMode::Signed => "Error",
+            ErrorMode::Absolute => "Absolute Error",
+            ErrorMode::Squared => "Squared Error",
+            ErrorMode::Relative => "Relative Error",
+        }
+    }
+
+    /// Name of exported columns in this mode
+    pub fn column_name(self) -> &'static str {
+        match self {
+            ErrorMode::Signed => "Error",
+            ErrorMode::Absolute => "AbsError",
+            ErrorMode::Squared => "SquaredError",
+            ErrorMode::Relative => "RelativeError",
+        }
+    }
+
+    /// An error of magnitude `magnitude` and the sign of `sign` as shown in this mode, None for
+    /// relative errors which need the reference too
+    pub fn display_magnitude(self, magnitude: f64, sign: f64) -> Option<f64> {
+        match self {
+            ErrorMode::Signed => Some(magnitude.copysign(sign)),
+            ErrorMode::Absolute => Some(magnitude),
+            ErrorMode::Squared => Some(magnitude * magnitude),
+            ErrorMode::Relative => None,
+        }
+    }
+
+    /// `errors` as shown in this mode, None if a relative error has nothing to divide by. Where the
+    /// reference is zero the relative error is missing.
+    pub fn apply<'a>(self, errors: &'a [f64], reference: Option<&[f64]>) -> Option<Cow<'a, [f64]>> {
+        Some(match self {
+            ErrorMode::Signed => Cow::Borrowed(errors),
+            ErrorMode::Absolute => Cow::Owned(errors.iter().map(|error| error.abs()).collect()),
+            ErrorMode::Squared => Cow::Owned(errors.iter().map(|error| error * error).collect()),
+            ErrorMode::Relative => Cow::Owned(
+                errors
+                    .iter()
+                    .zip(reference?)
+                    .map(|(&error, &reference)| if reference != 0.0 { error / reference.abs() } else { f64::NAN })
+                    .collect(),
+            ),
+        })
+    }
+}
@@ -0,0 +1,113 @@
+// Fan charts: the spread of a group of series per iteration, drawn as the median inside 10–90%
+// and min–max bands. Keeps hundreds of related variables readable in one chart.
+
+use std::ops::Range;
+
+use crate::error_mode::ErrorMode;
+use crate::smoothing::Smoothing;
+
+/// What the plots draw for the variables they show
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PlotMode {
+    #[default]
+    Lines, // One line per selected variable and run
+    Fan, // One fan per run over a group of variables
+}
+
+impl PlotMode {
+    pub const ALL: [PlotMode; 2] = [PlotMode::Lines, PlotMode::Fan];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PlotMode::Lines => "Lines",
+            PlotMode::Fan => "Fan chart",
+        }
+    }
+}
+
+/// What the fans of a plot were built from, to tell when they have to be rebuilt
+#[derive(Debug, Clone, PartialEq)]
+pub struct FanKey {
+    pub runs: Vec<(u64, u64)>, // Id and data generation of each run
+    pub vars: Vec<usize>,
+    pub mode: ErrorMode,
+    pub smoothing: Smoothing,
+}
+
+/// Quantiles across a group of series, per row of a run. NaN where none of the series has a
+/// sample.
+#[derive(Debug, Default)]
+pub struct FanBands {
+    pub min: Vec<f64>,
+    pub p10: Vec<f64>,
+    pub median: Vec<f64>,
+    pub p90: Vec<f64>,
+    pub max: Vec<f64>,
+}
+
+impl FanBands {
+    /// Bands of `series`, each lined up with the `len` rows of the same run
+    pub fn new(series: &[&[f64]], len: usize) -> Self {
+        let mut bands = FanBands::default();
+        let mut samples = Vec::with_capacity(series.len());
+        for row in 0..len {
+            samples.clear();
+            samples.extend(series.iter().filter_map(|series| series.get(row).copied()).filter(|val| !val.is_nan()));
+            samples.sort_by(f64::total_cmp);
+            bands.min.push(quantile(&samples, 0.0));
+            bands.p10.push(quantile(&samples, 0.1));
+            bands.median.push(quantile(&samples, 0.5));
+            bands.p90.push(quantile(&samples, 0.9));
+            bands.max.push(quantile(&samples, 1.0));
+        }
+        bands
+    }
+
+    /// The same bands with `f` applied to every quantile, e.g. to move them onto a log axis
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        let map = |quantiles: &[f64]| quantiles.iter().map(|&val| f(val)).collect();
+        FanBands { min: map(&self.min), p10: map(&self.p10), median: map(&self.median), p90: map(&self.p90), max: map(&self.max) }
+    }
+
+    /// The bands from the widest in, as (label, lower, upper)
+    pub fn bands(&self) -> [(&'static str, &[f64], &[f64]); 2] {
+        [("Min–max", &self.min, &self.max), ("10–90%", &self.p10, &self.p90)]
+    }
+
+    /// Every quantile with its name in exported column headers
+    pub fn columns(&self) -> [(&'static str, &[f64]); 5] {
+        [("Min", &self.min), ("P10", &self.p10), ("Median", &self.median), ("P90", &self.p90), ("Max", &self.max)]
+    }
+}
+
+/// Rows over which a band is drawn without a break: both of its edges are finite
+pub fn spans(lower: &[f64], upper: &[f64]) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (row, (low, high)) in lower.iter().zip(upper).enumerate() {
+        match (start, low.is_finite() && high.is_finite()) {
+            (None, true) => start = Some(row),
+            (Some(first), false) => {
+                spans.push(first..row);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(first) = start {
+        spans.push(first..lower.len().min(upper.len()));
+    }
+    spans
+}
+
+/// Quantile `q` of sorted samples, interpolating between the closest ranks
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    match sorted.len() {
+        0 => f64::NAN,
+        len => {
+            let rank = q * (len - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+        }
+    }
+}
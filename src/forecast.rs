@@ -0,0 +1,148 @@
+// Convergence-rate fits. The recent |error| history of a variable (or of an aggregate) is fitted
+// with an exponential or power-law decay, which projects how many more iterations it needs to
+// reach the tolerance.
+
+/// Shape of the decay fitted to the errors
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DecayModel {
+    #[default]
+    Exponential, // |error| = a·rⁿ, a line on a log axis
+    PowerLaw, // |error| = a·nᵏ, counting iterations from 1
+}
+
+impl DecayModel {
+    pub const ALL: [DecayModel; 2] = [DecayModel::Exponential, DecayModel::PowerLaw];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DecayModel::Exponential => "Exponential",
+            DecayModel::PowerLaw => "Power law",
+        }
+    }
+
+    /// The regressor of an iteration: the decay is linear in it after taking the log of |error|
+    fn regressor(self, iteration: f64) -> f64 {
+        match self {
+            DecayModel::Exponential => iteration,
+            DecayModel::PowerLaw => (iteration + 1.0).ln(),
+        }
+    }
+
+    fn iteration(self, regressor: f64) -> f64 {
+        match self {
+            DecayModel::Exponential => regressor,
+            DecayModel::PowerLaw => regressor.exp() - 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForecastSettings {
+    pub enabled: bool, // Fits are projected on the Error and aggregate plots
+    pub model: DecayModel,
+    pub window: usize, // Most recent samples fitted, 0 = the whole history
+}
+
+impl Default for ForecastSettings {
+    fn default() -> Self {
+        ForecastSettings { enabled: true, model: DecayModel::Exponential, window: 50 }
+    }
+}
+
+/// A decay fitted to |error| by least squares on log |error|
+#[derive(Debug, Clone, Copy)]
+pub struct DecayFit {
+    pub model: DecayModel,
+    intercept: f64,
+    slope: f64,
+    pub r_squared: f64, // Of the log-space fit
+    pub sign: f64, // Sign of the fitted errors taken together, for drawing the fit on signed errors
+    pub first: u32, // Iterations the fit covers
+    pub last: u32,
+}
+
+impl DecayFit {
+    /// Fit the last `settings.window` samples of `errors`. Zero and missing samples are skipped,
+    /// None with fewer than three samples left.
+    pub fn new(settings: &ForecastSettings, iterations: &[u32], errors: &[f64]) -> Option<Self> {
+        let samples: Vec<(u32, f64)> = iterations
+            .iter()
+            .copied()
+            .zip(errors.iter().copied())
+            .filter(|&(_, error)| error.is_finite() && error != 0.0)
+            .collect();
+        let keep = if settings.window == 0 { samples.len() } else { settings.window.min(samples.len()) };
+        let samples = &samples[samples.len() - keep..];
+        if samples.len() < 3 {
+            return None;
+        }
+        let sign = samples.iter().map(|&(_, error)| error).sum::<f64>().signum();
+        let samples: Vec<(u32, f64)> = samples.iter().map(|&(iteration, error)| (iteration, error.abs().ln())).collect();
+
+        let model = settings.model;
+        let count = samples.len() as f64;
+        let mean_x = samples.iter().map(|&(iteration, _)| model.regressor(iteration as f64)).sum::<f64>() / count;
+        let mean_y = samples.iter().map(|&(_, y)| y).sum::<f64>() / count;
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for &(iteration, y) in &samples {
+            let dx = model.regressor(iteration as f64) - mean_x;
+            let dy = y - mean_y;
+            sxx += dx * dx;
+            sxy += dx * dy;
+            syy += dy * dy;
+        }
+        if sxx == 0.0 {
+            return None;
+        }
+        let slope = sxy / sxx;
+        Some(DecayFit {
+            model,
+            intercept: mean_y - slope * mean_x,
+            slope,
+            r_squared: if syy == 0.0 { 1.0 } else { (sxy * sxy / (sxx * syy)).min(1.0) },
+            sign,
+            first: samples[0].0,
+            last: samples[samples.len() - 1].0,
+        })
+    }
+
+    /// Fitted |error| at `iteration`
+    pub fn predict(&self, iteration: f64) -> f64 {
+        (self.intercept + self.slope * self.model.regressor(iteration)).exp()
+    }
+
+    /// Iteration at which the fitted |error| falls below `tolerance`, None if it is not decaying
+    pub fn iteration_reaching(&self, tolerance: f64) -> Option<f64> {
+        if self.slope >= 0.0 || tolerance <= 0.0 {
+            return None;
+        }
+        let iteration = self.model.iteration((tolerance.ln() - self.intercept) / self.slope);
+        iteration.is_finite().then_some(iteration.max(0.0))
+    }
+
+    /// The decay rate in words, e.g. "×0.912 per iteration" or "∝ n^-1.35"
+    pub fn describe_rate(&self) -> String {
+        match self.model {
+            DecayModel::Exponential => format!("×{:.3} per iteration", self.slope.exp()),
+            DecayModel::PowerLaw => format!("∝ n^{:.2}", self.slope),
+        }
+    }
+
+    /// Points of the fitted curve from the start of the fit to `end`, for drawing the projection
+    pub fn curve(&self, end: f64) -> Vec<[f64; 2]> {
+        let start = self.first as f64;
+        let end = end.max(self.last as f64);
+        let steps = 100;
+        (0..=steps)
+            .map(|step| start + (end - start) * step as f64 / steps as f64)
+            .map(|iteration| [iteration, self.predict(iteration)])
+            .collect()
+    }
+
+    /// Where to stop the projection: where the tolerance is reached, but at most three times as far
+    /// ahead of the last fitted iteration as the fit reaches back
+    pub fn projection_end(&self, tolerance: f64) -> f64 {
+        let horizon = self.last as f64 + 3.0 * self.last.saturating_sub(self.first).max(10) as f64;
+        self.iteration_reaching(tolerance).map_or(horizon, |reach| reach.min(horizon))
+    }
+}
@@ -0,0 +1,77 @@
+// Variable groups parsed from names. XTMF names carry their structure in separators, e.g.
+// `AutoOwnership-1` or `ModeChoice:Transit:AM`, which the tree selector shows as nested groups.
+
+/// Characters splitting variable names into groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupSeparators(pub String);
+
+impl Default for GroupSeparators {
+    fn default() -> Self {
+        GroupSeparators(":-".to_string())
+    }
+}
+
+/// A group of variables sharing a name prefix, or the root of the tree
+#[derive(Debug, Default)]
+pub struct GroupNode {
+    pub name: String, // Segment of the name, empty for the root
+    pub path: String, // Prefix shared by the group's variables, unique within the tree
+    pub groups: Vec<GroupNode>,
+    pub leaves: Vec<(usize, String)>, // Variables directly in the group, with their last segment
+    pub vars: Vec<usize>, // Every variable in the group and its subgroups
+}
+
+impl GroupNode {
+    /// Tree of `vars` (ids into `names`) split at `separators`, keeping the order of `vars`.
+    /// Names without a separator sit at the root.
+    pub fn build(names: &[String], vars: &[usize], separators: &GroupSeparators) -> Self {
+        let mut root = GroupNode::default();
+        for &var in vars {
+            let name = &names[var];
+            let mut node = &mut root;
+            let mut rest = name.as_str();
+            while let Some(split) = rest.find(|c| separators.0.contains(c)) {
+                let segment = &rest[..split];
+                let path = &name[..name.len() - rest.len() + split];
+                node.vars.push(var);
+                // Names come sorted, so the group is usually the last one added
+                let last = node.groups.len().checked_sub(1).filter(|&last| node.groups[last].name == segment);
+                let idx = match last.or_else(|| node.groups.iter().position(|group| group.name == segment)) {
+                    Some(idx) => idx,
+                    None => {
+                        node.groups.push(GroupNode { name: segment.to_string(), path: path.to_string(), ..Default::default() });
+                        node.groups.len() - 1
+                    }
+                };
+                node = &mut node.groups[idx];
+                rest = &rest[split + rest[split..].chars().next().map_or(0, char::len_utf8)..];
+            }
+            node.vars.push(var);
+            node.leaves.push((var, rest.to_string()));
+        }
+        root
+    }
+
+    /// The group at `path`, searching the whole tree
+    pub fn find(&self, path: &str) -> Option<&GroupNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.groups.iter().find_map(|group| group.find(path))
+    }
+
+    /// Every group below this one, parents before their subgroups
+    pub fn descendants(&self) -> Vec<&GroupNode> {
+        let mut groups = Vec::new();
+        for group in &self.groups {
+            groups.push(group);
+            groups.extend(group.descendants());
+        }
+        groups
+    }
+
+    /// How many of the group's variables are selected
+    pub fn selected_count(&self, selected_vars: &[bool]) -> usize {
+        self.vars.iter().filter(|&&var| selected_vars.get(var).copied().unwrap_or(false)).count()
+    }
+}
@@ -0,0 +1,94 @@
+// Heatmap of many variables' errors over the iterations of a run, one row per variable. Shows at a
+// glance which of thousands of variables are still off when only a few fit on the line plots.
+
+use egui::{Color32, ColorImage};
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::error_mode::ErrorMode;
+
+/// Most iterations given a column each, longer runs are binned
+pub const MAX_COLUMNS: usize = 1024;
+
+/// Share of cells whose error fits inside the colour scale, the rest saturate. Keeps a few
+/// outliers from washing out everything else.
+const LIMIT_QUANTILE: f64 = 0.98;
+
+/// What a heatmap was built from, to tell when it has to be rebuilt
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapKey {
+    pub run_id: u64,
+    pub generation: u64, // Data generation of the run, which changes on reload and while following
+    pub vars: Vec<usize>,
+    pub mode: ErrorMode,
+    pub missing: Color32, // Colour of empty cells, which follows the theme
+}
+
+pub struct Heatmap {
+    pub vars: Vec<usize>, // App-wide id of each row's variable
+    pub columns: Vec<Range<usize>>, // Rows of the run binned into each column
+    pub cells: Vec<f64>, // Row-major, NaN where a bin has no sample
+    pub limit: f64, // Error shown in full colour
+}
+
+impl Heatmap {
+    /// Heatmap of the `errors` of each of `vars` over `len` rows. Variables without errors get no
+    /// row. A column covering several rows shows the error of largest magnitude among them.
+    pub fn new<'a>(vars: &[usize], len: usize, errors: impl Fn(usize) -> Option<Cow<'a, [f64]>>) -> Self {
+        let column_count = len.min(MAX_COLUMNS);
+        let columns: Vec<Range<usize>> = (0..column_count)
+            .map(|col| col * len / column_count..(col + 1) * len / column_count)
+            .collect();
+
+        let mut heatmap = Heatmap { vars: Vec::new(), columns, cells: Vec::new(), limit: 0.0 };
+        if column_count == 0 {
+            return heatmap;
+        }
+        for &var in vars {
+            let Some(errors) = errors(var) else { continue };
+            heatmap.vars.push(var);
+            for bin in &heatmap.columns {
+                let largest = errors[bin.clone()]
+                    .iter()
+                    .copied()
+                    .filter(|error| !error.is_nan())
+                    .max_by(|a, b| a.abs().total_cmp(&b.abs()));
+                heatmap.cells.push(largest.unwrap_or(f64::NAN));
+            }
+        }
+
+        let mut magnitudes: Vec<f64> = heatmap.cells.iter().filter(|cell| !cell.is_nan()).map(|cell| cell.abs()).collect();
+        if !magnitudes.is_empty() {
+            let idx = ((magnitudes.len() - 1) as f64 * LIMIT_QUANTILE).round() as usize;
+            heatmap.limit = *magnitudes.select_nth_unstable_by(idx, f64::total_cmp).1;
+        }
+        heatmap
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> f64 {
+        self.cells[row * self.columns.len() + col]
+    }
+
+    /// One pixel per cell
+    pub fn image(&self, missing: Color32) -> ColorImage {
+        if self.cells.is_empty() {
+            return ColorImage::filled([1, 1], missing); // Textures cannot be empty
+        }
+        let pixels = self.cells
+            .iter()
+            .map(|&cell| if cell.is_nan() { missing } else { diverging_color(cell / self.limit) })
+            .collect();
+        ColorImage::new([self.columns.len(), self.vars.len()], pixels)
+    }
+}
+
+/// Blue for negative, white for zero and red for positive `t`, saturating at ±1
+pub fn diverging_color(t: f64) -> Color32 {
+    let t = if t.is_finite() { t.clamp(-1.0, 1.0) } else { 0.0 };
+    let fade = |full: u8| (255.0 + (full as f64 - 255.0) * t.abs()).round() as u8;
+    if t < 0.0 {
+        Color32::from_rgb(fade(33), fade(102), fade(172))
+    } else {
+        Color32::from_rgb(fade(178), fade(24), fade(43))
+    }
+}
@@ -1,45 +1,189 @@
 // Hide console window in release builds on Windows
 #![cfg_attr(all(not(debug_assertions), target_os = "windows"), windows_subsystem = "windows")]
 
-use anyhow::{Context, Result};
-use csv::ReaderBuilder;
+use anyhow::Result;
 use egui::{Color32, RichText, Ui};
-use egui_plot::{Line, Plot, PlotPoints};
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::fs::File;
-
-#[derive(Debug, Deserialize)]
-struct CalibrationRecord {
-    #[serde(rename = "Iteration")]
-    iteration: u32,
-    #[serde(flatten)]
-    data: HashMap<String, f64>,
-}
+use egui_plot::{Line, Plot, PlotPoints, Points};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
+
+mod aggregate;
+mod axis;
+mod cli;
+mod columnar;
+mod compression;
+mod convergence;
+mod data;
+mod delta;
+mod dialect;
+mod error_mode;
+mod fan;
+mod forecast;
+mod grouping;
+mod heatmap;
+mod diff;
+mod run;
+mod phase;
+mod query;
+mod schema;
+mod smoothing;
+
+use data::{LoadProgress, LoadedReport, Tolerance, load_report};
+use aggregate::{AggregateMetric, AggregateScope, AggregateView, ErrorAggregate};
+use axis::{ScaledRange, YAxis, YScale, format_tick};
+use convergence::{GridSort, GridSortKey, SummaryColumn, SummarySort, VariableConvergence, sort_variables, summarize};
+use delta::DeltaPlots;
+use dialect::{DELIMITERS, ReadOptions, TextEncoding};
+use error_mode::ErrorMode;
+use fan::{FanBands, FanKey, PlotMode};
+use forecast::{DecayFit, DecayModel, ForecastSettings};
+use grouping::{GroupNode, GroupSeparators};
+use heatmap::{Heatmap, HeatmapKey, diverging_color};
+use diff::{RunDiff, error_difference};
+use phase::{PhaseTrajectory, iteration_color, ramp_position};
+use query::Query;
+use run::{Run, RunLineStyle, TailStatus};
+use schema::{ColumnSchema, MatchOn, SeriesRule};
+use smoothing::{Smoothing, SmoothingKind};
+
+/// Line colours shared by the interactive plots and the exported images
+const PLOT_COLORS: [Color32; 10] = [
+    Color32::RED, Color32::BLUE, Color32::GREEN, Color32::from_rgb(255, 165, 0),
+    Color32::from_rgb(128, 0, 128), Color32::from_rgb(165, 42, 42),
+    Color32::YELLOW, Color32::from_rgb(255, 192, 203), Color32::DARK_GRAY, Color32::BROWN,
+];
 
 #[derive(Default)]
 struct CalibrationApp {
-    records: Vec<CalibrationRecord>,
-    error_columns: Vec<String>,
-    value_columns: Vec<String>,
-    variable_names: Vec<String>, // Base variable names without Error:/Value: prefix
+    runs: Vec<Run>,
+    variable_names: Vec<String>, // Sorted union of the variable names of every run
+    next_run_id: u64,
     
     // UI State
     file_path: String,
-    file_loaded: bool,
     loading_error: Option<String>,
+    column_schema: ColumnSchema, // Applied to reports loaded from now on
+    read_options: ReadOptions, // Format overrides applied to reports loaded from now on
     
     // Plot selection - simplified to just variable selection
     selected_vars: Vec<bool>,
     prev_selected_vars: Vec<bool>, // Track previous selection to detect changes
     filter_text: String,
+    prev_filter_text: String, // Text the filter was last parsed from
+    filter_query: Query, // Last query that parsed, kept while the text has a syntax error
+    filter_error: Option<String>,
     focus_filter: bool, // Flag to focus filter input on next frame
     filter_has_focus: bool, // Track if filter currently has focus
+    variable_view: VariableView,
+    grid_sort: GridSort, // Order of the checkbox grid, with metrics from the convergence summary's run
+    group_separators: GroupSeparators, // Where the tree view splits names into groups
+    error_mode: ErrorMode, // How Error series are shown in the plot and its exports
+    error_axis: YAxis, // Y scale of the Error plot and its exported image
+    prev_error_axis: YAxis, // Track the previous scale to refit the Error plot when it changes
+    prev_error_mode: ErrorMode,
+    smoothing: HashMap<String, Smoothing>, // Per series kind, plots without an entry are not smoothed
+    delta_plots: DeltaPlots, // Step plots shown after the plots of the series themselves
+    phase_var: Option<usize>, // Variable of the phase plot, None = first selected variable
+    forecast: ForecastSettings, // Decay fits projected on the Error and aggregate plots
+    plot_mode: PlotMode,
+    fan_group: Option<String>, // Group path the fan charts summarize, None = the filtered variables
+    fans: HashMap<String, (FanKey, Vec<(u64, FanBands)>)>, // Fan of each run by plot kind, rebuilt when its key changes
     
     // Theme state
     is_dark_mode: Option<bool>, // None = follow system, Some(true) = force dark, Some(false) = force
+    
+    // Live follow state
+    follow_mode: bool,
+    follow_paused: bool,
+    last_tail_poll: Option<Instant>,
+    
+    // Reports being parsed on worker threads
+    load_tasks: Vec<LoadTask>,
+    queued_adds: Vec<String>, // Reports to add once the report being opened has replaced the runs
+    
+    // Run comparison
+    view_mode: ViewMode,
+    diff_baseline: Option<u64>, // None = first run
+    diff_candidate: Option<u64>, // None = last run other than the baseline
+    convergence_tolerance: Tolerance,
+    
+    // Convergence summary table
+    summary_run: Option<u64>, // None = first run
+    summary_sort: SummarySort,
+    
+    // Aggregate objective panel
+    aggregate_view: AggregateView,
+    
+    // Error heatmap
+    heatmap_run: Option<u64>, // None = first run
+    heatmap: Option<(HeatmapKey, Heatmap, egui::TextureHandle)>, // Rebuilt when its key changes
+}
+
+/// How several loaded runs are compared
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ViewMode {
+    #[default]
+    Overlay, // Every run drawn on the same Error/Value plots
+    Diff, // Candidate run against a baseline run
+}
+
+/// How the variable checkboxes are laid out
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum VariableView {
+    #[default]
+    Grid, // Flat columns of checkboxes
+    Tree, // Nested groups parsed from the names
+}
+
+/// A report load running in the background
+struct LoadTask {
+    target: LoadTarget,
+    path: String,
+    progress: Arc<LoadProgress>,
+    receiver: mpsc::Receiver<Result<LoadedReport>>,
 }
 
+/// What a finished load does with its report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoadTarget {
+    Replace, // Becomes the only run
+    Add, // Added alongside the loaded runs
+    Reload(u64), // Refreshes the run with this id
+}
+
+/// One line on a plot: the Error or Value samples of a variable in one run
+struct PlotSeries<'a> {
+    var: usize, // App-wide variable id
+    var_name: &'a str,
+    run: &'a Run,
+    samples: Cow<'a, [f64]>, // Error samples are in the chosen error mode
+    smoothed: Option<Vec<f64>>, // Present when the plot is smoothed
+    color: Color32,
+    legend: String,
+}
+
+/// Extensions offered when browsing for reports, including compressed .csv.gz and .csv.zst
+const REPORT_EXTENSIONS: [&str; 7] = ["csv", "gz", "zst", "parquet", "arrow", "feather", "arrows"];
+
+/// Tooltip of the filter box
+const FILTER_HELP: &str = "Filter variables by name. Terms next to each other must all match, \
+commas, | or OR separate alternatives and parentheses group them. A term is a case-insensitive \
+substring, a glob such as Auto*-? matched against the whole name, a \"quoted phrase\" or a \
+/regex/ (/regex/i ignores case); -Transit or NOT Transit excludes matches. Compare the metrics \
+of the convergence summary's run with final_error, final_abs_error, final_value, max_abs_error, \
+converged_at or converged, e.g. final_abs_error > 0.05 or converged = false.
+Press Ctrl+F to focus this field, Esc to clear when focused.";
+
+/// Most quads drawn per band of an interactive fan chart, longer runs are decimated
+const FAN_QUADS: usize = 500;
+
+/// How often the report is checked for appended rows while following
+const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 impl CalibrationApp {
     fn apply_theme(&self, ctx: &egui::Context) {
         match self.is_dark_mode {
@@ -49,139 +193,418 @@ impl CalibrationApp {
         }
     }
     
+    /// Load a report synchronously as an additional run, used where no UI is running
     fn load_file(&mut self, path: String) -> Result<()> {
-        println!("Starting to load file: {path}");
+        let report = load_report(&path, &self.column_schema, &self.read_options, &LoadProgress::default())?;
+        self.apply_loaded_report(report, LoadTarget::Add);
+        Ok(())
+    }
+    
+    /// Parse the report named in the file box on a worker thread, replacing every loaded run.
+    /// The current runs stay in place until the new report has loaded successfully.
+    fn try_load_file(&mut self) {
+        self.start_load(self.file_path.clone(), LoadTarget::Replace);
+    }
+    
+    /// Reload every run from disk, keeping labels and line styles
+    fn reload_runs(&mut self) {
+        let single_run_path = match &self.runs[..] {
+            [run] => Some(run.path.as_str()),
+            _ => None,
+        };
+        if self.runs.is_empty() || single_run_path.is_some_and(|path| path != self.file_path) {
+            // A different file was typed into the file box, load that one instead
+            self.try_load_file();
+            return;
+        }
         
-        let file = File::open(&path)
-            .with_context(|| format!("Failed to open file: {path}"))?;
+        let runs: Vec<(u64, String)> = self.runs.iter().map(|run| (run.id, run.path.clone())).collect();
+        for (id, path) in runs {
+            self.start_load(path, LoadTarget::Reload(id));
+        }
+    }
+    
+    fn start_load(&mut self, path: String, target: LoadTarget) {
+        // Runs added or reloaded while a report is being opened would be dropped when it replaces
+        // them. Adding waits for it instead, reloading is moot.
+        let replacing = self.load_tasks.iter().any(|task| task.target == LoadTarget::Replace);
+        match target {
+            LoadTarget::Replace => self.queued_adds.clear(),
+            LoadTarget::Add if replacing => {
+                self.queued_adds.push(path);
+                return;
+            }
+            LoadTarget::Reload(_) if replacing => return,
+            LoadTarget::Add | LoadTarget::Reload(_) => {}
+        }
+        
+        // A newer load makes older ones with the same outcome pointless
+        self.load_tasks.retain(|task| {
+            let superseded = target == LoadTarget::Replace || (task.target == target && target != LoadTarget::Add);
+            if superseded {
+                task.progress.cancelled.store(true, Ordering::Relaxed);
+            }
+            !superseded
+        });
         
-        let mut rdr = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
-        println!("Number of Columns {}", rdr.headers()?.len());
+        let progress = Arc::new(LoadProgress::default());
+        let (sender, receiver) = mpsc::channel();
+        let thread_progress = Arc::clone(&progress);
+        let thread_path = path.clone();
+        let schema = self.column_schema.clone();
+        let read_options = self.read_options;
+        std::thread::spawn(move || {
+            // The receiver is gone if this load was replaced, nothing to report then
+            let _ = sender.send(load_report(&thread_path, &schema, &read_options, &thread_progress));
+        });
         
-        let mut records: Vec<CalibrationRecord> = Vec::new();
-        let mut record_count = 0;
+        self.load_tasks.push(LoadTask { target, path, progress, receiver });
+    }
+    
+    /// Apply the results of background loads that have finished
+    fn poll_load_tasks(&mut self) {
+        let mut finished = Vec::new();
+        self.load_tasks.retain(|task| {
+            let result = match task.receiver.try_recv() {
+                Ok(result) => result,
+                Err(mpsc::TryRecvError::Empty) => return true,
+                Err(mpsc::TryRecvError::Disconnected) => Err(anyhow::anyhow!("Loading stopped unexpectedly")),
+            };
+            finished.push((task.target, task.progress.cancelled.load(Ordering::Relaxed), result));
+            false
+        });
         
-        for result in rdr.deserialize() {
-            let record: CalibrationRecord = result
-                .with_context(|| format!("Failed to parse CSV record at line {}", record_count + 2))?;
-            records.push(record);
-            record_count += 1;
-            
-            // Add progress feedback for large files
-            if record_count % 100 == 0 {
-                println!("Loaded {record_count} records...");
+        for (target, cancelled, result) in finished {
+            match result {
+                Ok(report) => self.apply_loaded_report(report, target),
+                // Cancelling keeps whatever was loaded before
+                Err(_) if cancelled => {}
+                Err(e) => {
+                    self.loading_error = Some(e.to_string());
+                    if target == LoadTarget::Replace {
+                        self.runs.clear();
+                        self.rebuild_variables();
+                    }
+                }
             }
         }
         
-        println!("Finished loading {record_count} records");
-        
-        if records.is_empty() {
-            return Err(anyhow::anyhow!("No records found in file"));
+        if !self.load_tasks.iter().any(|task| task.target == LoadTarget::Replace) {
+            for path in std::mem::take(&mut self.queued_adds) {
+                self.start_load(path, LoadTarget::Add);
+            }
+        }
+    }
+    
+    fn apply_loaded_report(&mut self, report: LoadedReport, target: LoadTarget) {
+        match target {
+            LoadTarget::Replace => {
+                // A different report starts with a fresh selection
+                if report.data.variable_names != self.variable_names {
+                    self.selected_vars.clear();
+                    self.prev_selected_vars.clear();
+                }
+                self.file_path = report.path.clone();
+                self.runs.clear();
+                self.runs.push(Run::new(self.next_run_id, report, RunLineStyle::nth(0)));
+                self.next_run_id += 1;
+            }
+            LoadTarget::Add => {
+                if self.runs.is_empty() {
+                    self.file_path = report.path.clone();
+                }
+                let line_style = RunLineStyle::nth(self.runs.len());
+                self.runs.push(Run::new(self.next_run_id, report, line_style));
+                self.next_run_id += 1;
+            }
+            LoadTarget::Reload(id) => {
+                // The run may have been removed while it was reloading
+                let Some(run) = self.runs.iter_mut().find(|run| run.id == id) else {
+                    return;
+                };
+                run.replace_report(report);
+            }
         }
         
-        // Extract column names
-        let error_columns: Vec<String> = records[0]
-            .data
-            .keys()
-            .filter(|k| k.starts_with("Error:"))
-            .cloned()
-            .collect();
-        
-        let value_columns: Vec<String> = records[0]
-            .data
-            .keys()
-            .filter(|k| k.starts_with("Value:"))
-            .cloned()
+        self.rebuild_variables();
+        self.loading_error = None;
+    }
+    
+    /// Recompute the variable list from the loaded runs, keeping the selection of variables that
+    /// are still present
+    fn rebuild_variables(&mut self) {
+        let mut variable_names: Vec<String> = self.runs
+            .iter()
+            .flat_map(|run| run.data.variable_names.iter().cloned())
             .collect();
+        variable_names.sort();
+        variable_names.dedup();
         
-        // Create unified variable names (base names without Error:/Value: prefix)
-        let mut variable_names = std::collections::HashSet::new();
+        let was_selected = |selection: &[bool], name: &String| {
+            self.variable_names
+                .binary_search(name)
+                .is_ok_and(|old_id| selection.get(old_id).copied().unwrap_or(false))
+        };
+        self.selected_vars = variable_names.iter().map(|name| was_selected(&self.selected_vars, name)).collect();
+        self.prev_selected_vars = variable_names.iter().map(|name| was_selected(&self.prev_selected_vars, name)).collect();
         
-        for col in &error_columns {
-            if let Some(base_name) = col.strip_prefix("Error:") {
-                variable_names.insert(base_name.trim().to_string());
+        for run in &mut self.runs {
+            run.var_ids = variable_names
+                .iter()
+                .map(|name| run.data.variable_names.binary_search(name).ok())
+                .collect();
+        }
+        self.variable_names = variable_names;
+    }
+    
+    fn remove_run(&mut self, id: u64) {
+        self.runs.retain(|run| run.id != id);
+        self.load_tasks.retain(|task| {
+            let reloading = task.target == LoadTarget::Reload(id);
+            if reloading {
+                task.progress.cancelled.store(true, Ordering::Relaxed);
+            }
+            !reloading
+        });
+        self.rebuild_variables();
+    }
+    
+    /// Parse the rows appended to every run's file since the last load or poll, keeping the
+    /// current selection and plot views. Runs whose file was truncated are reloaded.
+    fn poll_tail(&mut self) -> Result<()> {
+        let mut truncated = Vec::new();
+        let mut new_variables = false;
+        let mut first_error = None;
+        for run in &mut self.runs {
+            match run.poll_tail() {
+                Ok(TailStatus::Truncated) => truncated.push((run.id, run.path.clone())),
+                Ok(TailStatus::NewVariables) => new_variables = true,
+                Ok(_) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
             }
         }
         
-        for col in &value_columns {
-            if let Some(base_name) = col.strip_prefix("Value:") {
-                variable_names.insert(base_name.trim().to_string());
-            }
+        if new_variables {
+            self.rebuild_variables();
         }
         
-        let mut variable_names: Vec<String> = variable_names.into_iter().collect();
-        variable_names.sort();
-
-        // Update state
+        // The report was rewritten (e.g. a new calibration started), start over
+        for (id, path) in truncated {
+            self.start_load(path, LoadTarget::Reload(id));
+        }
         
-        // Check to see if we are being reloaded
-        if self.file_loaded && variable_names == self.variable_names {
-            // If the variable names are the same, restore the previous selection
-            self.selected_vars = self.prev_selected_vars.clone();   
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
-        else {
-            // If the variable names have changed, reset the selection
-            self.selected_vars = vec![false; variable_names.len()];
-            self.prev_selected_vars = vec![false; variable_names.len()];
+    }
+    
+    /// Baseline and candidate run of the diff view, if there are two runs to compare
+    fn diff_runs(&self) -> Option<(&Run, &Run)> {
+        if self.runs.len() < 2 {
+            return None;
         }
-
-        // If we are loading a new file, reset the previous selection
-        // Update the rest of the variables.
-        self.records = records;
-        self.error_columns = error_columns;
-        self.value_columns = value_columns;
-        self.variable_names = variable_names;
-        self.file_loaded = true;
-        self.loading_error = None;
+        let baseline = self.diff_baseline
+            .and_then(|id| self.runs.iter().find(|run| run.id == id))
+            .unwrap_or(&self.runs[0]);
+        let candidate = self.diff_candidate
+            .and_then(|id| self.runs.iter().find(|run| run.id == id))
+            .unwrap_or_else(|| self.runs.iter().rev().find(|run| run.id != baseline.id).unwrap_or(baseline));
+        Some((baseline, candidate))
+    }
+    
+    /// Series kinds of the loaded runs, Error and Value first
+    fn series_kinds(&self) -> Vec<&str> {
+        let mut kinds: Vec<&str> = Vec::new();
+        for kind in self.runs.iter().flat_map(|run| &run.data.kinds) {
+            if !kinds.contains(&kind.as_str()) {
+                kinds.push(kind);
+            }
+        }
+        kinds
+    }
+    
+    /// The kinds of every plot to show: the series kinds followed by the enabled step plots
+    fn plot_kinds(&self) -> Vec<String> {
+        let kinds = self.series_kinds();
+        let mut plot_kinds: Vec<String> = kinds.iter().map(|kind| kind.to_string()).collect();
+        for (kind, enabled) in [(schema::VALUE, self.delta_plots.value), (schema::ERROR, self.delta_plots.error)] {
+            if enabled && kinds.contains(&kind) {
+                plot_kinds.push(delta::delta_kind(kind));
+            }
+        }
+        plot_kinds
+    }
+    
+    /// Every line to draw on the plot of one series kind. Each variable keeps one colour across runs
+    /// and runs are told apart by line style.
+    fn plot_series<'a>(&'a self, selected_variables: &[(usize, &'a String)], plot_type: &str, colors: &[Color32]) -> Vec<PlotSeries<'a>> {
+        let mut plot_series = Vec::new();
+        let mut plot_idx = 0;
+        let smoothing = self.smoothing(plot_type);
+        let kind = delta::base_kind(plot_type).unwrap_or(plot_type);
         
-        Ok(())
+        for &(var_idx, var_name) in selected_variables {
+            let color = colors[plot_idx % colors.len()];
+            let mut has_series = false;
+            for run in &self.runs {
+                let Some(samples) = run.series(kind, var_idx) else { continue };
+                let samples = if kind == schema::ERROR {
+                    let Some(samples) = self.displayed_errors(run, var_idx) else { continue };
+                    samples
+                } else {
+                    Cow::Borrowed(samples)
+                };
+                let samples = if kind != plot_type { Cow::Owned(delta::first_difference(&samples)) } else { samples };
+                
+                let legend = if self.runs.len() > 1 {
+                    format!("{var_name} [{}]", run.label)
+                } else {
+                    var_name.clone()
+                };
+                let smoothed = smoothing.is_enabled().then(|| smoothing.apply(&samples));
+                plot_series.push(PlotSeries { var: var_idx, var_name, run, samples, smoothed, color, legend });
+                has_series = true;
+            }
+            if has_series {
+                plot_idx += 1;
+            }
+        }
+        
+        plot_series
+    }
+    
+    /// Error samples of a variable in the chosen error mode
+    fn displayed_errors<'a>(&self, run: &'a Run, var: usize) -> Option<Cow<'a, [f64]>> {
+        let reference = run.series(error_mode::TARGET, var).or_else(|| run.value_series(var));
+        self.error_mode.apply(run.error_series(var)?, reference)
+    }
+    
+    /// Decay fitted to the errors of a variable, None if there are too few of them
+    fn fit_errors(&self, run: &Run, var: usize) -> Option<DecayFit> {
+        DecayFit::new(&self.forecast, &run.data.iterations, run.error_series(var)?)
+    }
+    
+    /// The fitted decay of a series' |error|, in the error mode and on the Y scale of the Error plot,
+    /// projected to where it reaches the tolerance. None when projections are off or there is no fit.
+    fn error_projection(&self, series: &PlotSeries, axis: YAxis) -> Option<Vec<[f64; 2]>> {
+        if !self.forecast.enabled {
+            return None;
+        }
+        let fit = self.fit_errors(series.run, series.var)?;
+        let points: Vec<[f64; 2]> = fit.curve(fit.projection_end(self.convergence_tolerance.0))
+            .into_iter()
+            .map(|[iteration, magnitude]| Some([iteration, axis.transform(self.error_mode.display_magnitude(magnitude, fit.sign)?)]))
+            .collect::<Option<_>>()?;
+        // Negative errors have no place on a log axis
+        points.iter().all(|[_, y]| y.is_finite()).then_some(points)
     }
     
-    fn filter_columns(&self, columns: &[String]) -> Vec<String> {
-        let filtered: Vec<String> = columns
+    /// The fan of each run over the plotted series, from their smoothed samples if the plot is
+    /// smoothed. Runs without any of the series get no fan.
+    fn fan_bands<'a>(&'a self, plot_series: &[PlotSeries]) -> Vec<(&'a Run, FanBands)> {
+        self.runs
             .iter()
-            .filter(|col| {
-                if self.filter_text.is_empty() {
-                    true
-                } else {
-                    let filter_terms: Vec<&str> = self.filter_text.split(',').map(|s| s.trim()).collect();
-                    filter_terms.iter().any(|term| col.to_lowercase().contains(&term.to_lowercase()))
-                }
+            .filter_map(|run| {
+                let samples: Vec<&[f64]> = plot_series
+                    .iter()
+                    .filter(|series| series.run.id == run.id)
+                    .map(|series| series.smoothed.as_deref().unwrap_or(&series.samples))
+                    .collect();
+                (!samples.is_empty()).then(|| (run, FanBands::new(&samples, run.data.len())))
             })
-            .cloned()
-            .collect();
-        
-        filtered
+            .collect()
+    }
+    
+    /// Line colour of a run's fan
+    fn run_color(&self, run: &Run) -> Color32 {
+        let run_idx = self.runs.iter().position(|other| other.id == run.id).unwrap_or(0);
+        PLOT_COLORS[run_idx % PLOT_COLORS.len()]
+    }
+    
+    fn smoothing(&self, plot_type: &str) -> Smoothing {
+        self.smoothing.get(plot_type).copied().unwrap_or_default()
+    }
+    
+    /// The columns exported for the plotted series: each series' samples, followed by its smoothed
+    /// samples when the plot is smoothed. `name` builds a column name from a series and a suffix.
+    fn export_columns<'a>(&self, plot_series: &'a [PlotSeries], plot_type: &str, name: impl Fn(&PlotSeries, &str) -> String) -> Vec<(String, &'a Run, &'a [f64])> {
+        let suffix = format!(" {}", self.smoothing(plot_type).label());
+        let mut columns = Vec::new();
+        for series in plot_series {
+            columns.push((name(series, ""), series.run, &series.samples[..]));
+            if let Some(smoothed) = &series.smoothed {
+                columns.push((name(series, &suffix), series.run, &smoothed[..]));
+            }
+        }
+        columns
+    }
+    
+    /// The columns exported for fan charts: every quantile of each run's fan. `name` builds a column
+    /// name from a run and the quantile's name.
+    fn fan_columns<'a>(&self, fans: &'a [(&'a Run, FanBands)], name: impl Fn(&Run, &str) -> String) -> Vec<(String, &'a Run, &'a [f64])> {
+        fans.iter()
+            .flat_map(|(run, fan)| fan.columns().map(|(quantile, samples)| (name(run, quantile), *run, samples)))
+            .collect()
+    }
+    
+    /// Y axis label of the plot of a series kind
+    fn axis_label(&self, plot_type: &str) -> String {
+        match delta::base_kind(plot_type) {
+            Some(kind) => format!("{} {}", delta::DELTA, self.axis_label(kind)),
+            None if plot_type == schema::ERROR => self.error_mode.axis_label().to_string(),
+            None => plot_type.to_string(),
+        }
     }
     
-    fn has_error_column(&self, var_name: &str) -> bool {
-        self.error_columns.iter().any(|col| {
-            col.strip_prefix("Error:").map(|s| s.trim()) == Some(var_name)
-        })
+    /// Name of a series kind in exported column headers, e.g. `AbsError` for absolute errors or
+    /// `DeltaValue` for value steps
+    fn column_name(&self, plot_type: &str) -> String {
+        match delta::base_kind(plot_type) {
+            Some(kind) => format!("Delta{}", self.column_name(kind)),
+            None if plot_type == schema::ERROR => self.error_mode.column_name().to_string(),
+            None => plot_type.to_string(),
+        }
     }
     
-    fn has_value_column(&self, var_name: &str) -> bool {
-        self.value_columns.iter().any(|col| {
-            col.strip_prefix("Value:").map(|s| s.trim()) == Some(var_name)
-        })
+    /// Ids of the variables matching the filter box, with metrics taken from the run of the
+    /// convergence summary
+    fn filter_variables(&self) -> Vec<usize> {
+        let run = self.convergence_run().filter(|_| self.filter_query.uses_metrics());
+        (0..self.variable_names.len())
+            .filter(|&var| {
+                let metrics = run.and_then(|run| VariableConvergence::new(run, var, self.convergence_tolerance.0));
+                self.filter_query.matches(&self.variable_names[var], metrics.as_ref())
+            })
+            .collect()
     }
     
-    fn get_error_column_name(&self, var_name: &str) -> Option<String> {
-        self.error_columns.iter().find(|col| {
-            col.strip_prefix("Error:").map(|s| s.trim()) == Some(var_name)
-        }).cloned()
+    /// Parse the filter box again if its text changed
+    fn update_filter_query(&mut self) {
+        if self.filter_text == self.prev_filter_text {
+            return;
+        }
+        self.prev_filter_text = self.filter_text.clone();
+        match Query::parse(&self.filter_text) {
+            Ok(query) => {
+                self.filter_query = query;
+                self.filter_error = None;
+            }
+            Err(e) => self.filter_error = Some(format!("{e:#}")),
+        }
     }
     
-    fn get_value_column_name(&self, var_name: &str) -> Option<String> {
-        self.value_columns.iter().find(|col| {
-            col.strip_prefix("Value:").map(|s| s.trim()) == Some(var_name)
-        }).cloned()
+    /// Run of the convergence summary, whose metrics the filter compares
+    fn convergence_run(&self) -> Option<&Run> {
+        self.summary_run
+            .and_then(|id| self.runs.iter().find(|run| run.id == id))
+            .or(self.runs.first())
     }
     
     fn save_plot_csv(&self, selected_variables: &[(usize, &String)], plot_type: &str) -> Result<()> {
-        let default_filename = format!("{}_plot_data.csv", plot_type.to_lowercase());
+        let default_filename = format!("{}_plot_data.csv", file_stem(plot_type));
         
         if let Some(path) = rfd::FileDialog::new()
             .add_filter("CSV Files", &["csv"])
@@ -192,35 +615,38 @@ impl CalibrationApp {
             let mut writer = csv::Writer::from_path(path)?;
             
             // Write header
+            let plot_series = self.plot_series(selected_variables, plot_type, &PLOT_COLORS);
+            let column_name = self.column_name(plot_type);
+            let fans = if self.plot_mode == PlotMode::Fan { self.fan_bands(&plot_series) } else { Vec::new() };
+            let export_columns = if self.plot_mode == PlotMode::Fan {
+                self.fan_columns(&fans, |run, quantile| {
+                    if self.runs.len() > 1 {
+                        format!("{quantile}_{column_name} [{}]", run.label)
+                    } else {
+                        format!("{quantile}_{column_name}")
+                    }
+                })
+            } else {
+                self.export_columns(&plot_series, plot_type, |series, suffix| {
+                    if self.runs.len() > 1 {
+                        format!("{}_{column_name}{suffix} [{}]", series.var_name, series.run.label)
+                    } else {
+                        format!("{}_{column_name}{suffix}", series.var_name)
+                    }
+                })
+            };
             let mut header = vec!["Iteration".to_string()];
-            for (_, var_name) in selected_variables {
-                if plot_type == "Error" && self.has_error_column(var_name) {
-                    header.push(format!("{var_name}_Error"));
-                } else if plot_type == "Value" && self.has_value_column(var_name) {
-                    header.push(format!("{var_name}_Value"));
-                }
-            }
+            header.extend(export_columns.iter().map(|(name, _, _)| name.clone()));
             writer.write_record(&header)?;
             
-            // Write data
-            for record in &self.records {
-                let mut row = vec![record.iteration.to_string()];
-                for (_, var_name) in selected_variables {
-                    if plot_type == "Error" && self.has_error_column(var_name) {
-                        if let Some(error_col) = self.get_error_column_name(var_name) {
-                            if let Some(&val) = record.data.get(&error_col) {
-                                row.push(val.to_string());
-                            } else {
-                                row.push("".to_string());
-                            }
-                        }
-                    } else if plot_type == "Value" && self.has_value_column(var_name)
-                            && let Some(value_col) = self.get_value_column_name(var_name) {
-                        if let Some(&val) = record.data.get(&value_col) {
-                            row.push(val.to_string());
-                        } else {
-                            row.push("".to_string());
-                        }
+            // Write data lined up by iteration, leaving missing samples empty
+            let (iterations, columns) = self.aligned_samples(&export_columns);
+            for (row_idx, iteration) in iterations.iter().enumerate() {
+                let mut row = vec![iteration.to_string()];
+                for column in &columns {
+                    match column[row_idx] {
+                        Some(val) => row.push(val.to_string()),
+                        None => row.push(String::new()),
                     }
                 }
                 writer.write_record(&row)?;
@@ -231,8 +657,68 @@ impl CalibrationApp {
         Ok(())
     }
     
+    /// Write the plotted series as Parquet, with columns named like report columns (`<Kind>:<var>`).
+    /// Only a plot of the series as they were loaded keeps their kinds, so only then does the file
+    /// load back as a report.
+    fn save_plot_parquet(&self, selected_variables: &[(usize, &String)], plot_type: &str) -> Result<()> {
+        let default_filename = format!("{}_plot_data.parquet", file_stem(plot_type));
+        
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("Parquet Files", &["parquet"])
+            .set_file_name(&default_filename)
+            .set_title(format!("Save {plot_type} Plot Data as Parquet"))
+            .save_file()
+        {
+            let plot_series = self.plot_series(selected_variables, plot_type, &PLOT_COLORS);
+            let column_name = self.column_name(plot_type);
+            let fans = if self.plot_mode == PlotMode::Fan { self.fan_bands(&plot_series) } else { Vec::new() };
+            let export_columns = if self.plot_mode == PlotMode::Fan {
+                self.fan_columns(&fans, |run, quantile| {
+                    if self.runs.len() > 1 {
+                        format!("{column_name}:{quantile} [{}]", run.label)
+                    } else {
+                        format!("{column_name}:{quantile}")
+                    }
+                })
+            } else {
+                self.export_columns(&plot_series, plot_type, |series, suffix| {
+                    if self.runs.len() > 1 {
+                        format!("{column_name}:{}{suffix} [{}]", series.var_name, series.run.label)
+                    } else {
+                        format!("{column_name}:{}{suffix}", series.var_name)
+                    }
+                })
+            };
+            let (iterations, samples) = self.aligned_samples(&export_columns);
+            let columns: Vec<(String, Vec<Option<f64>>)> = export_columns
+                .into_iter()
+                .zip(samples)
+                .map(|((name, _, _), samples)| (name, samples))
+                .collect();
+            columnar::write_parquet(&path, &iterations, &columns)?;
+        }
+        Ok(())
+    }
+    
+    /// The iterations of every run, and each column's samples lined up with them (None where the
+    /// column has no sample for an iteration)
+    fn aligned_samples(&self, export_columns: &[(String, &Run, &[f64])]) -> (Vec<u32>, Vec<Vec<Option<f64>>>) {
+        let iterations: BTreeSet<u32> = self.runs.iter().flat_map(|run| run.data.iterations.iter().copied()).collect();
+        let columns = export_columns
+            .iter()
+            .map(|(_, run, samples)| {
+                let samples: HashMap<u32, f64> = run.data.iterations.iter().copied().zip(samples.iter().copied()).collect();
+                iterations
+                    .iter()
+                    .map(|iteration| samples.get(iteration).copied().filter(|val| !val.is_nan()))
+                    .collect()
+            })
+            .collect();
+        (iterations.into_iter().collect(), columns)
+    }
+    
     fn save_plot_image(&self, selected_variables: &[(usize, &String)], plot_type: &str, colors: &[Color32], plot_bounds: Option<&egui_plot::PlotBounds>, ctx: &egui::Context) -> Result<()> {
-        let default_filename = format!("{}_plot.png", plot_type.to_lowercase());
+        let default_filename = format!("{}_plot.png", file_stem(plot_type));
         
         if let Some(path) = rfd::FileDialog::new()
             .add_filter("PNG Images", &["png"])
@@ -240,165 +726,341 @@ impl CalibrationApp {
             .set_title(format!("Save {plot_type} Plot Image"))
             .save_file()
         {
-            use plotters::prelude::*;
-            
             // Detect current theme from egui context
             let is_dark_mode = ctx.global_style().visuals.dark_mode;
-            let bg_color = if is_dark_mode {
-                RGBColor(32, 32, 32) // Dark background
-            } else {
-                WHITE // Light background
-            };
-            let text_color = if is_dark_mode {
-                RGBColor(255, 255, 255) // White text for dark mode
-            } else {
-                RGBColor(0, 0, 0) // Black text for light mode
-            };
-            let grid_color = if is_dark_mode {
-                RGBColor(64, 64, 64) // Light gray grid lines for dark mode
-            } else {
-                RGBColor(128, 128, 128) // Dark gray grid lines for light mode
+            self.render_plot_image(&path, selected_variables, plot_type, colors, plot_bounds, is_dark_mode)?;
+        }
+        Ok(())
+    }
+    
+    fn render_plot_image(&self, path: &Path, selected_variables: &[(usize, &String)], plot_type: &str, colors: &[Color32], plot_bounds: Option<&egui_plot::PlotBounds>, is_dark_mode: bool) -> Result<()> {
+        use plotters::prelude::*;
+        
+        let bg_color = if is_dark_mode {
+            RGBColor(32, 32, 32) // Dark background
+        } else {
+            WHITE // Light background
+        };
+        let text_color = if is_dark_mode {
+            RGBColor(255, 255, 255) // White text for dark mode
+        } else {
+            RGBColor(0, 0, 0) // Black text for light mode
+        };
+        let grid_color = if is_dark_mode {
+            RGBColor(64, 64, 64) // Light gray grid lines for dark mode
+        } else {
+            RGBColor(128, 128, 128) // Dark gray grid lines for light mode
+        };
+        
+        let plot_series = self.plot_series(selected_variables, plot_type, colors);
+        // Samples on the Y scale of the plot, which is where the interactive plot bounds are too
+        let axis = if plot_type == schema::ERROR { self.error_axis } else { YAxis::default() };
+        let scaled_samples: Vec<Vec<f64>> = plot_series
+            .iter()
+            .map(|series| series.samples.iter().map(|&val| axis.transform(val)).collect())
+            .collect();
+        let fans: Vec<(&Run, FanBands)> = match self.plot_mode {
+            PlotMode::Lines => Vec::new(),
+            PlotMode::Fan => self.fan_bands(&plot_series)
+                .into_iter()
+                .map(|(run, fan)| (run, fan.map(|val| axis.transform(val))))
+                .collect(),
+        };
+        
+        // Dashed error projections, which reach past the last iteration
+        let projections: Vec<Option<Vec<[f64; 2]>>> = plot_series
+            .iter()
+            .map(|series| {
+                let projected = plot_type == schema::ERROR && self.plot_mode == PlotMode::Lines;
+                projected.then(|| self.error_projection(series, axis)).flatten()
+            })
+            .collect();
+        
+        let root = BitMapBackend::new(path, (1600, 1200)).into_drawing_area();
+        root.fill(&bg_color)?;
+        
+        // Use plot bounds if provided, otherwise calculate from data
+        let (x_range, y_range) = if let Some(bounds) = plot_bounds {
+            let x_min = bounds.min()[0];
+            let x_max = bounds.max()[0];
+            let y_min = bounds.min()[1];
+            let y_max = bounds.max()[1];
+            (x_min..x_max, y_min..y_max)
+        } else {
+            // Fallback to calculating from all data, at the iteration numbers the samples are drawn at
+            let projected_points = projections.iter().flatten().flatten();
+            let x_range = {
+                let iterations = self.runs.iter().flat_map(|run| run.data.iterations.iter().map(|&iteration| iteration as f64));
+                let (min_x, max_x) = iterations
+                    .chain(projected_points.clone().map(|[x, _]| *x))
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| (min.min(x), max.max(x)));
+                if min_x > max_x {
+                    0.0..1.0 // No data
+                } else if min_x == max_x {
+                    min_x - 0.5..max_x + 0.5
+                } else {
+                    min_x..max_x
+                }
             };
-            
-            let root = BitMapBackend::new(&path, (1600, 1200)).into_drawing_area();
-            root.fill(&bg_color)?;
-            
-            // Use plot bounds if provided, otherwise calculate from data
-            let (x_range, y_range) = if let Some(bounds) = plot_bounds {
-                let x_min = bounds.min()[0];
-                let x_max = bounds.max()[0];
-                let y_min = bounds.min()[1];
-                let y_max = bounds.max()[1];
-                (x_min..x_max, y_min..y_max)
-            } else {
-                // Fallback to calculating from all data
-                let x_range = 0f64..self.records.len() as f64;
-                let y_range = {
-                    let mut min_val = f64::INFINITY;
-                    let mut max_val = f64::NEG_INFINITY;
-                    
-                    for (_, var_name) in selected_variables {
-                        if plot_type == "Error" && self.has_error_column(var_name) {
-                            if let Some(error_col) = self.get_error_column_name(var_name) {
-                                for record in &self.records {
-                                    if let Some(&val) = record.data.get(&error_col) {
-                                        min_val = min_val.min(val);
-                                        max_val = max_val.max(val);
-                                    }
-                                }
-                            }
-                        } else if plot_type == "Value" && self.has_value_column(var_name)
-                                && let Some(value_col) = self.get_value_column_name(var_name) {
-                            for record in &self.records {
-                                if let Some(&val) = record.data.get(&value_col) {
-                                    min_val = min_val.min(val);
-                                    max_val = max_val.max(val);
-                                }
-                            }
-                        }
-                    }
-                    
+            let y_range = {
+                let mut min_val = f64::INFINITY;
+                let mut max_val = f64::NEG_INFINITY;
+                
+                let extents: Vec<&[f64]> = match self.plot_mode {
+                    PlotMode::Lines => scaled_samples.iter().map(|samples| &samples[..]).collect(),
+                    PlotMode::Fan => fans.iter().flat_map(|(_, fan)| [&fan.min[..], &fan.max[..]]).collect(),
+                };
+                let projected = projected_points.map(|[_, y]| *y);
+                for val in extents.into_iter().flatten().copied().chain(projected).filter(|v| !v.is_nan()) {
+                    min_val = min_val.min(val);
+                    max_val = max_val.max(val);
+                }
+                
+                if min_val > max_val {
+                    0.0..1.0 // No data
+                } else {
+                    // A constant series still gets a band around it
                     let range = max_val - min_val;
-                    let margin = range * 0.1;
+                    let margin = if range > 0.0 { range * 0.1 } else { min_val.abs().max(1.0) * 0.1 };
                     (min_val - margin)..(max_val + margin)
-                };
-                (x_range, y_range)
+                }
             };
-            
-            let mut chart = ChartBuilder::on(&root)
-                .caption(format!("{plot_type} Convergence"), ("Arial", 60).into_font().color(&text_color))
-                .margin(40)
-                .x_label_area_size(100)
-                .y_label_area_size(160)
-                .build_cartesian_2d(x_range, y_range)?;
-            
-            chart
-                .configure_mesh()
-                .x_desc("Iteration")
-                .y_desc(if plot_type == "Error" { "Absolute Error" } else { "Value" })
-                .axis_desc_style(("Arial", 30).into_font().color(&text_color))
-                .label_style(("Arial", 24).into_font().color(&text_color))
-                .axis_style(text_color)
-                .light_line_style(grid_color)
-                .bold_line_style(grid_color)
-                .draw()?;
-            
-            let mut plot_idx = 0;
-            for (_, var_name) in selected_variables {
-                if plot_type == "Error" && self.has_error_column(var_name) {
-                    if let Some(error_col) = self.get_error_column_name(var_name) {
-                        let points: Vec<(f64, f64)> = self.records
-                            .iter()
-                            .filter_map(|r| {
-                                r.data.get(&error_col).map(|&val| (r.iteration as f64, val))
-                            })
-                            .collect();
-                        
-                        let color = colors[plot_idx % colors.len()];
-                        let rgb_color = RGBColor(color.r(), color.g(), color.b());
-                        
-                        chart.draw_series(LineSeries::new(points, &rgb_color))?
-                            .label(*var_name)
-                            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], rgb_color));
-                        
-                        plot_idx += 1;
-                    }
-                } else if plot_type == "Value" && self.has_value_column(var_name) 
-                        && let Some(value_col) = self.get_value_column_name(var_name){
-                    let points: Vec<(f64, f64)> = self.records
-                        .iter()
-                        .filter_map(|r| {
-                            r.data.get(&value_col).map(|&val| (r.iteration as f64, val))
-                        })
+            (x_range, y_range)
+        };
+        
+        let smoothing = self.smoothing(plot_type);
+        let mut caption = format!("{plot_type} Convergence");
+        if self.plot_mode == PlotMode::Fan {
+            caption.push_str(&format!(" across {} variables", selected_variables.len()));
+        }
+        if smoothing.is_enabled() {
+            caption.push_str(&format!(", {}", smoothing.label()));
+        }
+        let mut chart = ChartBuilder::on(&root)
+            .caption(caption, ("Arial", 60).into_font().color(&text_color))
+            .margin(40)
+            .x_label_area_size(100)
+            .y_label_area_size(160)
+            .build_cartesian_2d(x_range, ScaledRange::new(y_range, axis))?;
+        
+        let y_desc = self.axis_label(plot_type);
+        let y_desc = if axis.is_linear() { y_desc.to_string() } else { format!("{y_desc} ({})", axis.scale.label().to_lowercase()) };
+        chart
+            .configure_mesh()
+            .x_desc("Iteration")
+            .y_desc(y_desc)
+            .axis_desc_style(("Arial", 30).into_font().color(&text_color))
+            .label_style(("Arial", 24).into_font().color(&text_color))
+            .axis_style(text_color)
+            .light_line_style(grid_color)
+            .bold_line_style(grid_color)
+            .draw()?;
+        
+        // A fan is drawn as its bands, the widest and faintest first, under the median line
+        for (run, fan) in &fans {
+            let color = self.run_color(run);
+            let rgb_color = RGBColor(color.r(), color.g(), color.b());
+            let legend = |label: &str| if self.runs.len() > 1 { format!("{label} [{}]", run.label) } else { label.to_string() };
+            let x = |row: usize| run.data.iterations[row] as f64;
+            for ((label, lower, upper), opacity) in fan.bands().into_iter().zip([0.15, 0.3]) {
+                let style = rgb_color.mix(opacity).filled();
+                for (span_idx, span) in fan::spans(lower, upper).into_iter().enumerate() {
+                    let outline: Vec<(f64, f64)> = span.clone()
+                        .map(|row| (x(row), upper[row]))
+                        .chain(span.rev().map(|row| (x(row), lower[row])))
                         .collect();
-                    
-                    let color = colors[plot_idx % colors.len()];
-                    let rgb_color = RGBColor(color.r(), color.g(), color.b());
-                    
-                    chart.draw_series(LineSeries::new(points, &rgb_color))?
-                        .label(*var_name)
-                        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], rgb_color));
-                    
-                    plot_idx += 1;
+                    let series = chart.draw_series(std::iter::once(Polygon::new(outline, style)))?;
+                    if span_idx == 0 {
+                        series
+                            .label(legend(label))
+                            .legend(move |(x, y)| Rectangle::new([(x, y - 6), (x + 10, y + 6)], style));
+                    }
                 }
             }
             
-            chart.configure_series_labels()
-                .background_style(bg_color.mix(0.8))
-                .border_style(text_color)
-                .label_font(("Arial", 24).into_font().color(&text_color))
-                .position(plotters::chart::SeriesLabelPosition::UpperRight)
-                .margin(20)
-                .draw()?;
-            root.present()?;
+            let style = rgb_color.stroke_width(3);
+            for (segment_idx, segment) in run.data.segments(&fan.median).into_iter().enumerate() {
+                let series = if let [[x, y]] = segment[..] {
+                    chart.draw_series(std::iter::once(Circle::new((x, y), 4, style.filled())))?
+                } else {
+                    let points = segment.into_iter().map(|[x, y]| (x, y));
+                    match run.line_style {
+                        RunLineStyle::Solid => chart.draw_series(LineSeries::new(points, style))?,
+                        RunLineStyle::Dashed => chart.draw_series(DashedLineSeries::new(points, 12, 8, style))?,
+                        RunLineStyle::Dotted => chart.draw_series(DashedLineSeries::new(points, 2, 6, style))?,
+                    }
+                };
+                if segment_idx == 0 {
+                    series
+                        .label(legend("Median"))
+                        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style));
+                }
+            }
+        }
+        
+        if self.plot_mode == PlotMode::Lines {
+            for ((plot_series, samples), projection) in plot_series.iter().zip(&scaled_samples).zip(&projections) {
+                let color = plot_series.color;
+                let rgb_color = RGBColor(color.r(), color.g(), color.b());
+                
+                // A smoothed series is drawn thick over its faded raw samples and takes the legend entry
+                let smoothed: Option<Vec<f64>> = plot_series.smoothed
+                    .as_ref()
+                    .map(|smoothed| smoothed.iter().map(|&val| axis.transform(val)).collect());
+                let raw_style = if smoothed.is_some() { rgb_color.mix(0.3).stroke_width(1) } else { rgb_color.stroke_width(1) };
+                let lines = [
+                    Some((samples, raw_style, smoothed.is_none())),
+                    smoothed.as_ref().map(|smoothed| (smoothed, rgb_color.stroke_width(3), true)),
+                ];
+                
+                for (samples, style, has_legend) in lines.into_iter().flatten() {
+                    // Draw each gap-free run separately, only the first one gets a legend entry
+                    for (segment_idx, segment) in plot_series.run.data.segments(samples).into_iter().enumerate() {
+                        let series = if let [[x, y]] = segment[..] {
+                            // An isolated sample has no line to draw, mark it instead
+                            chart.draw_series(std::iter::once(Circle::new((x, y), 4, style.filled())))?
+                        } else {
+                            let points = segment.into_iter().map(|[x, y]| (x, y));
+                            match plot_series.run.line_style {
+                                RunLineStyle::Solid => chart.draw_series(LineSeries::new(points, style))?,
+                                RunLineStyle::Dashed => chart.draw_series(DashedLineSeries::new(points, 12, 8, style))?,
+                                RunLineStyle::Dotted => chart.draw_series(DashedLineSeries::new(points, 2, 6, style))?,
+                            }
+                        };
+                        if has_legend && segment_idx == 0 {
+                            series
+                                .label(plot_series.legend.as_str())
+                                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style));
+                        }
+                    }
+                }
+                
+                if let Some(points) = projection {
+                    let points = points.iter().map(|&[x, y]| (x, y));
+                    chart.draw_series(DashedLineSeries::new(points, 6, 4, rgb_color.stroke_width(2)))?;
+                }
+            }
         }
+        
+        chart.configure_series_labels()
+            .background_style(bg_color.mix(0.8))
+            .border_style(text_color)
+            .label_font(("Arial", 24).into_font().color(&text_color))
+            .position(plotters::chart::SeriesLabelPosition::UpperRight)
+            .margin(20)
+            .draw()?;
+        root.present()?;
         Ok(())
     }
 }
 
-impl eframe::App for CalibrationApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Apply theme at the beginning of each frame
-        self.apply_theme(ctx);
-        
-        // Handle keyboard shortcuts
-        ctx.input(|i| {
-            // Ctrl+F to focus filter
-            if i.modifiers.ctrl && i.key_pressed(egui::Key::F) {
-                self.focus_filter = true;
-            }
-            
-            // Escape to clear filter when filter has focus
-            if i.key_pressed(egui::Key::Escape) && self.filter_has_focus {
-                self.filter_text.clear();
-            }
+/// Style the checkboxes of `ui` with a variable's graph colour
+fn tint_checkbox(ui: &mut Ui, graph_color: Color32) {
+    let mut checkbox_style = ui.style().visuals.widgets.inactive;
+    checkbox_style.bg_fill = graph_color;
+    checkbox_style.bg_stroke = egui::Stroke::new(1.0, graph_color.gamma_multiply(0.8));
+    
+    let mut active_style = ui.style().visuals.widgets.active;
+    active_style.bg_fill = graph_color;
+    active_style.bg_stroke = egui::Stroke::new(2.0, graph_color.gamma_multiply(0.8));
+    
+    ui.style_mut().visuals.widgets.inactive = checkbox_style;
+    ui.style_mut().visuals.widgets.active = active_style;
+}
+
+/// The subgroups of `node` as collapsible rows, each with a tri-state checkbox selecting the whole
+/// group and a count of its selected variables, followed by the variables directly in `node`
+fn show_group_tree(ui: &mut Ui, node: &GroupNode, variable_names: &[String], selected_vars: &mut [bool], color_of: &dyn Fn(usize) -> Option<Color32>) {
+    for group in &node.groups {
+        let selected = group.selected_count(selected_vars);
+        let id = ui.make_persistent_id(("variable_group", &group.path));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+            .show_header(ui, |ui| {
+                let mut all_selected = selected == group.vars.len();
+                let checkbox = egui::Checkbox::new(&mut all_selected, format!("📁 {}", group.name))
+                    .indeterminate(selected > 0 && selected < group.vars.len());
+                if ui.add(checkbox).changed() {
+                    for &var in &group.vars {
+                        selected_vars[var] = all_selected;
+                    }
+                }
+                ui.weak(format!("{selected}/{}", group.vars.len()));
+            })
+            .body(|ui| show_group_tree(ui, group, variable_names, selected_vars, color_of));
+    }
+    
+    for (var, leaf) in &node.leaves {
+        ui.scope(|ui| {
+            if selected_vars[*var]
+                && let Some(color) = color_of(*var) {
+                tint_checkbox(ui, color);
+            }
+            ui.checkbox(&mut selected_vars[*var], format!("📈 {leaf}"))
+                .on_hover_text(variable_names[*var].as_str());
+        });
+    }
+}
+
+/// Legend of a colour ramp from `low` to `high`, `color` giving the colour at 0 to 1 along it.
+/// Returns the label of the high end, to hang a tooltip on.
+fn color_ramp_legend(ui: &mut Ui, low: String, high: String, color: impl Fn(f64) -> Color32) -> egui::Response {
+    ui.label(low);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(120.0, 12.0), egui::Sense::hover());
+    let steps = 24;
+    for step in 0..steps {
+        let x = |step: usize| rect.left() + rect.width() * step as f32 / steps as f32;
+        let band = egui::Rect::from_x_y_ranges(x(step)..=x(step + 1), rect.y_range());
+        ui.painter().rect_filled(band, 0.0, color(step as f64 / (steps - 1) as f64));
+    }
+    ui.label(high)
+}
+
+/// Lowercase stem of the files a plot is exported to, e.g. `value` or `delta_value`
+fn file_stem(plot_type: &str) -> String {
+    plot_type.replace(delta::DELTA, "delta_").to_lowercase()
+}
+
+impl eframe::App for CalibrationApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Apply theme at the beginning of each frame
+        self.apply_theme(ctx);
+        
+        // Handle keyboard shortcuts
+        ctx.input(|i| {
+            // Ctrl+F to focus filter
+            if i.modifiers.ctrl && i.key_pressed(egui::Key::F) {
+                self.focus_filter = true;
+            }
+            
+            // Escape to clear filter when filter has focus
+            if i.key_pressed(egui::Key::Escape) && self.filter_has_focus {
+                self.filter_text.clear();
+            }
 
-            // Reload file on F5, if one has been selected
+            // Reload the runs on F5, if a file has been selected
             if i.key_pressed(egui::Key::F5) && !self.file_path.is_empty() {
-                self.try_load_file();
+                self.reload_runs();
             }
         });
         
+        // Swap in finished background loads, keep repainting for the progress bars until then
+        if !self.load_tasks.is_empty() {
+            self.poll_load_tasks();
+            ctx.request_repaint_after(Duration::from_millis(50));
+        }
+        
+        // Pick up newly written rows while following the reports
+        if self.follow_mode && !self.follow_paused && !self.runs.is_empty() && self.load_tasks.is_empty() {
+            let due = self.last_tail_poll.is_none_or(|t| t.elapsed() >= TAIL_POLL_INTERVAL);
+            if due {
+                self.last_tail_poll = Some(Instant::now());
+                match self.poll_tail() {
+                    Ok(()) => self.loading_error = None,
+                    Err(e) => self.loading_error = Some(format!("Live update failed: {e:#}")),
+                }
+            }
+            ctx.request_repaint_after(TAIL_POLL_INTERVAL);
+        }
     }
 
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
@@ -434,7 +1096,7 @@ impl eframe::App for CalibrationApp {
                 ui.text_edit_singleline(&mut self.file_path);
                 if ui.button("📁 Browse & Load File").clicked() 
                 && let Some(path) = rfd::FileDialog::new()
-                        .add_filter("CSV Files", &["csv"])
+                        .add_filter("CSV Reports", &REPORT_EXTENSIONS)
                         .add_filter("All Files", &["*"])
                         .set_title("Select Calibration CSV File")
                         .pick_file() {
@@ -442,16 +1104,113 @@ impl eframe::App for CalibrationApp {
                     self.try_load_file();
                 }
                 
+                if !self.runs.is_empty()
+                && ui.button("➕ Add Run").on_hover_text("Load another report to overlay on the same plots").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("CSV Reports", &REPORT_EXTENSIONS)
+                        .add_filter("All Files", &["*"])
+                        .set_title("Select Calibration CSV File to Compare")
+                        .pick_file() {
+                    self.start_load(path.display().to_string(), LoadTarget::Add);
+                }
+                
                 if !self.file_path.is_empty() && ui.button("🔄 Reload").clicked() {
-                    self.try_load_file();
+                    self.reload_runs();
+                }
+                
+                if !self.runs.is_empty() {
+                    ui.separator();
+                    ui.checkbox(&mut self.follow_mode, "📡 Follow")
+                        .on_hover_text("Watch the files and add rows as they are appended by a running calibration");
+                    
+                    if self.follow_mode {
+                        let pause_text = if self.follow_paused { "▶ Resume" } else { "⏸ Pause" };
+                        if ui.button(pause_text).clicked() {
+                            self.follow_paused = !self.follow_paused;
+                        }
+                        
+                        if self.follow_paused {
+                            ui.colored_label(Color32::GRAY, "⏸ Paused");
+                        } else {
+                            ui.colored_label(Color32::from_rgb(0, 200, 0), "● LIVE");
+                        }
+                        if let [run] = &self.runs[..] {
+                            ui.label(format!("{} iterations", run.data.len()));
+                        }
+                    }
                 }
             });
             
+            self.show_schema_section(ui);
+            self.show_format_section(ui);
+            
+            // Loaded runs, only worth listing once there is something to compare
+            if self.runs.len() > 1 {
+                self.show_runs_section(ui);
+            }
+            
+            for task in &self.load_tasks {
+                let total_bytes = task.progress.total_bytes.load(Ordering::Relaxed);
+                let bytes_read = task.progress.bytes_read.load(Ordering::Relaxed);
+                let rows_parsed = task.progress.rows_parsed.load(Ordering::Relaxed);
+                let fraction = if total_bytes > 0 { bytes_read as f32 / total_bytes as f32 } else { 0.0 };
+                let file_name = Path::new(&task.path).file_name().map_or(task.path.clone(), |name| name.to_string_lossy().into_owned());
+                let mut cancel = false;
+                
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(400.0)
+                            .text(format!(
+                                "Loading {file_name}… {:.1} / {:.1} MB, {rows_parsed} rows",
+                                bytes_read as f64 / 1_048_576.0,
+                                total_bytes as f64 / 1_048_576.0,
+                            )),
+                    );
+                    cancel = ui.button("✖ Cancel").clicked();
+                });
+                
+                if cancel {
+                    task.progress.cancelled.store(true, Ordering::Relaxed);
+                }
+            }
+            for path in &self.queued_adds {
+                ui.weak(format!("Waiting to add {path}"));
+            }
+            
             if let Some(error) = &self.loading_error {
                 ui.colored_label(Color32::RED, format!("❌ Error: {error}"));
             }
             
-            if !self.file_loaded {
+            for run in self.runs.iter().filter(|run| !run.data.skipped_cells.is_empty()) {
+                let total_skipped: usize = run.data.skipped_cells.values().sum();
+                let mut skipped: Vec<(&String, &usize)> = run.data.skipped_cells.iter().collect();
+                skipped.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+                let run_prefix = if self.runs.len() > 1 { format!("{}: ", run.label) } else { String::new() };
+                
+                egui::CollapsingHeader::new(
+                    RichText::new(format!("⚠ {run_prefix}Skipped {total_skipped} missing or non-numeric cells in {} columns", skipped.len()))
+                        .color(Color32::from_rgb(255, 165, 0)),
+                )
+                .id_salt(("skipped_cells", run.id))
+                .show(ui, |ui| {
+                    ui.small("Gaps are left out of the plots. Rows without a valid Iteration are dropped.");
+                    egui::ScrollArea::vertical()
+                        .id_salt(("skipped_cells_scroll", run.id))
+                        .max_height(150.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new(("skipped_cells_grid", run.id)).striped(true).show(ui, |ui| {
+                                for (column, count) in skipped {
+                                    ui.label(column.as_str());
+                                    ui.label(count.to_string());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                });
+            }
+            
+            if self.runs.is_empty() {
                 ui.colored_label(Color32::GRAY, "Load a calibration CSV file to begin analysis");
                 return;
             }
@@ -474,7 +1233,13 @@ impl eframe::App for CalibrationApp {
                 }
                 
                 // Add tooltip with more information
-                filter_response.on_hover_text("Filter variables by name. Use commas to separate multiple terms. Press Ctrl+F to focus this field, Esc to clear when focused.");
+                filter_response.on_hover_text(FILTER_HELP);
+                
+                self.update_filter_query();
+                if let Some(error) = &self.filter_error {
+                    ui.colored_label(Color32::RED, format!("❌ {error}"))
+                        .on_hover_text("The last valid filter stays applied until this is fixed");
+                }
                 
                 // Add hint about keyboard shortcuts
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -485,18 +1250,215 @@ impl eframe::App for CalibrationApp {
             ui.separator();
             
             // Variable selection and plotting
-        let ctx = ui.ctx().clone();
         egui::ScrollArea::vertical().show(ui, |ui| {
-            self.show_variables_section(ui, &ctx);
+            self.show_variables_section(ui);
         });
     }
 }
 
 impl CalibrationApp {
-    fn show_variables_section(&mut self, ui: &mut Ui, ctx: &egui::Context) {
+    /// Editor for the rules that decide which columns hold which kind of series
+    fn show_schema_section(&mut self, ui: &mut Ui) {
+        let loaded_schema_differs = self.runs.iter().any(|run| run.schema != self.column_schema);
+        
+        egui::CollapsingHeader::new("🧩 Column Schema")
+            .id_salt("column_schema")
+            .show(ui, |ui| {
+                ui.small("Columns are matched against these rules in order. The rest of the header names the variable; a regex uses its first capture group if it has one.");
+                
+                let mut remove = None;
+                egui::Grid::new("column_schema_grid").striped(true).show(ui, |ui| {
+                    for (rule_idx, rule) in self.column_schema.rules.iter_mut().enumerate() {
+                        // Error and Value are built in, only their patterns can change
+                        let built_in = rule_idx < 2;
+                        ui.add_enabled(!built_in, egui::TextEdit::singleline(&mut rule.kind).desired_width(120.0).hint_text("Kind, e.g. Target"));
+                        egui::ComboBox::from_id_salt(("schema_match_on", rule_idx))
+                            .width(80.0)
+                            .selected_text(rule.match_on.label())
+                            .show_ui(ui, |ui| {
+                                for match_on in MatchOn::ALL {
+                                    ui.selectable_value(&mut rule.match_on, match_on, match_on.label());
+                                }
+                            });
+                        ui.add(egui::TextEdit::singleline(&mut rule.pattern).desired_width(200.0));
+                        if !built_in && ui.small_button("✖").on_hover_text("Remove this rule").clicked() {
+                            remove = Some(rule_idx);
+                        }
+                        if let Some(error) = rule.error() {
+                            ui.colored_label(Color32::RED, error);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(rule_idx) = remove {
+                    self.column_schema.rules.remove(rule_idx);
+                }
+                
+                ui.horizontal(|ui| {
+                    if ui.button("➕ Add Series Kind").clicked() {
+                        self.column_schema.rules.push(SeriesRule::new("", MatchOn::Prefix, ""));
+                    }
+                    if ui.button("↺ Defaults").clicked() {
+                        self.column_schema = ColumnSchema::default();
+                    }
+                    if loaded_schema_differs {
+                        ui.colored_label(Color32::from_rgb(255, 165, 0), "Reload to apply the schema to the loaded runs");
+                    }
+                });
+            });
+    }
+    
+    /// Delimiter, encoding and decimal separator overrides, each detected from the file when Auto
+    fn show_format_section(&mut self, ui: &mut Ui) {
+        let loaded_format_differs = self.runs.iter().any(|run| run.is_text() && !run.dialect.satisfies(&self.read_options));
+        // Parquet and Arrow columns are typed, there is nothing to override
+        let only_columnar = !self.runs.is_empty() && !self.runs.iter().any(Run::is_text);
+        
+        egui::CollapsingHeader::new("🔤 File Format")
+            .id_salt("file_format")
+            .show(ui, |ui| {
+                let options = &mut self.read_options;
+                if !only_columnar {
+                    ui.horizontal(|ui| {
+                        ui.label("Delimiter:");
+                        let delimiter_label = options.delimiter
+                            .and_then(|delimiter| DELIMITERS.iter().find(|&&(d, _)| d == delimiter))
+                            .map_or("Auto", |&(_, name)| name);
+                        egui::ComboBox::from_id_salt("read_delimiter")
+                            .width(90.0)
+                            .selected_text(delimiter_label)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut options.delimiter, None, "Auto");
+                                for (delimiter, name) in DELIMITERS {
+                                    ui.selectable_value(&mut options.delimiter, Some(delimiter), name);
+                                }
+                            });
+                    
+                        ui.label("Encoding:");
+                        egui::ComboBox::from_id_salt("read_encoding")
+                            .width(110.0)
+                            .selected_text(options.encoding.map_or("Auto", TextEncoding::label))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut options.encoding, None, "Auto");
+                                for encoding in TextEncoding::ALL {
+                                    ui.selectable_value(&mut options.encoding, Some(encoding), encoding.label());
+                                }
+                            });
+                    
+                        ui.label("Decimal:");
+                        let decimal_label = match options.decimal_comma {
+                            None => "Auto",
+                            Some(false) => "Point (0.5)",
+                            Some(true) => "Comma (0,5)",
+                        };
+                        egui::ComboBox::from_id_salt("read_decimal")
+                            .width(110.0)
+                            .selected_text(decimal_label)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut options.decimal_comma, None, "Auto");
+                                ui.selectable_value(&mut options.decimal_comma, Some(false), "Point (0.5)");
+                                ui.selectable_value(&mut options.decimal_comma, Some(true), "Comma (0,5)");
+                            });
+                    
+                        if ui.button("↺ Auto").clicked() {
+                            *options = ReadOptions::default();
+                        }
+                    });
+                }
+                
+                for run in &self.runs {
+                    let format = if run.is_text() { run.dialect.describe() } else { run.format.label().to_string() };
+                    ui.small(format!("{}: {format}", run.label));
+                }
+                if loaded_format_differs {
+                    ui.colored_label(Color32::from_rgb(255, 165, 0), "Reload to apply the format to the loaded runs");
+                }
+            });
+    }
+    
+    fn show_runs_section(&mut self, ui: &mut Ui) {
+        let mut reload = None;
+        let mut remove = None;
+
+        egui::CollapsingHeader::new(format!("📂 Runs ({})", self.runs.len()))
+            .id_salt("runs")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::Grid::new("runs_grid").striped(true).show(ui, |ui| {
+                    for run in &mut self.runs {
+                        egui::ComboBox::from_id_salt(("run_style", run.id))
+                            .width(80.0)
+                            .selected_text(run.line_style.label())
+                            .show_ui(ui, |ui| {
+                                for style in RunLineStyle::ALL {
+                                    ui.selectable_value(&mut run.line_style, style, style.label());
+                                }
+                            });
+                        ui.add(egui::TextEdit::singleline(&mut run.label).desired_width(160.0))
+                            .on_hover_text("Label shown in the plot legends");
+                        ui.label(format!("{} iterations", run.data.len()));
+                        ui.small(&run.path);
+                        if ui.small_button("🔄").on_hover_text("Reload this run").clicked() {
+                            reload = Some((run.id, run.path.clone()));
+                        }
+                        if ui.small_button("✖").on_hover_text("Remove this run").clicked() {
+                            remove = Some(run.id);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("View:");
+                    ui.selectable_value(&mut self.view_mode, ViewMode::Overlay, "📊 Overlay");
+                    ui.selectable_value(&mut self.view_mode, ViewMode::Diff, "🔀 Diff")
+                        .on_hover_text("Compare a candidate run against a baseline run");
+                });
+
+                if self.view_mode == ViewMode::Diff {
+                    let (baseline_id, candidate_id) = match self.diff_runs() {
+                        Some((baseline, candidate)) => (baseline.id, candidate.id),
+                        None => return,
+                    };
+                    let label_of = |id: u64| self.runs.iter().find(|run| run.id == id).map_or("", |run| run.label.as_str());
+
+                    ui.horizontal(|ui| {
+                        ui.label("Baseline:");
+                        egui::ComboBox::from_id_salt("diff_baseline")
+                            .selected_text(label_of(baseline_id))
+                            .show_ui(ui, |ui| {
+                                for run in &self.runs {
+                                    if ui.selectable_label(run.id == baseline_id, &run.label).clicked() {
+                                        self.diff_baseline = Some(run.id);
+                                    }
+                                }
+                            });
+                        ui.label("Candidate:");
+                        egui::ComboBox::from_id_salt("diff_candidate")
+                            .selected_text(label_of(candidate_id))
+                            .show_ui(ui, |ui| {
+                                for run in &self.runs {
+                                    if ui.selectable_label(run.id == candidate_id, &run.label).clicked() {
+                                        self.diff_candidate = Some(run.id);
+                                    }
+                                }
+                            });
+                    });
+                }
+            });
+
+        if let Some((id, path)) = reload {
+            self.start_load(path, LoadTarget::Reload(id));
+        }
+        if let Some(id) = remove {
+            self.remove_run(id);
+        }
+    }
+
+    fn show_variables_section(&mut self, ui: &mut Ui) {
         ui.label(RichText::new("Variables").heading());
         
-        let filtered_vars = self.filter_columns(&self.variable_names);
+        let filtered_vars = self.filter_variables();
         
         if filtered_vars.is_empty() {
             ui.label("No variables match the current filter");
@@ -509,99 +1471,138 @@ impl CalibrationApp {
         // Show variable count
         ui.horizontal(|ui| {
             ui.label(format!("📊 Showing {total_vars} variables"));
+            
+            ui.separator();
+            ui.selectable_value(&mut self.variable_view, VariableView::Grid, "▦ Grid");
+            ui.selectable_value(&mut self.variable_view, VariableView::Tree, "🌲 Tree");
+            if self.variable_view == VariableView::Tree {
+                ui.label("split at");
+                ui.add(egui::TextEdit::singleline(&mut self.group_separators.0).desired_width(40.0))
+                    .on_hover_text("Each of these characters separates a group from the rest of a name, e.g. \":-\" puts ModeChoice:Transit:AM under ModeChoice › Transit");
+            } else {
+                ui.label("sorted by");
+                egui::ComboBox::from_id_salt("grid_sort")
+                    .selected_text(self.grid_sort.key.label())
+                    .show_ui(ui, |ui| {
+                        for key in GridSortKey::ALL {
+                            ui.selectable_value(&mut self.grid_sort.key, key, key.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Metrics are taken from the run of the convergence summary. Value Change adds up how far the Value moved between iterations, Error/Value Only puts variables missing one of the two columns first.");
+                let direction = if self.grid_sort.descending { "⏷ Descending" } else { "⏶ Ascending" };
+                if ui.button(direction).clicked() {
+                    self.grid_sort.descending = !self.grid_sort.descending;
+                }
+            }
+            
+            ui.separator();
+            ui.label("Converged below |error|:");
+            ui.add(egui::DragValue::new(&mut self.convergence_tolerance.0).speed(0.001).range(0.0..=f64::MAX))
+                .on_hover_text("A variable has converged from the first iteration after which its absolute error stays below this");
         });
         
         ui.separator();
         
         // Get selected variables and create color mapping
-        let selected_variables: Vec<(usize, &String)> = self.variable_names
-            .iter()
-            .enumerate()
-            .filter(|(i, _)| *i < self.selected_vars.len() && self.selected_vars[*i])
+        let selected_ids: Vec<usize> = (0..self.variable_names.len())
+            .filter(|&i| i < self.selected_vars.len() && self.selected_vars[i])
             .collect();
         
-        let colors = [
-            Color32::RED, Color32::BLUE, Color32::GREEN, Color32::from_rgb(255, 165, 0),
-            Color32::from_rgb(128, 0, 128), Color32::from_rgb(165, 42, 42),
-            Color32::YELLOW, Color32::from_rgb(255, 192, 203), Color32::DARK_GRAY, Color32::BROWN,
-        ];
+        let colors = PLOT_COLORS;
         
-        // Create a mapping from variable name to color index for selected variables
+        // Create a mapping from variable id to color index for selected variables
         let mut variable_color_map = std::collections::HashMap::new();
         let mut color_idx = 0;
-        for (_, var_name) in &selected_variables {
-            if self.has_error_column(var_name) || self.has_value_column(var_name) {
-                variable_color_map.insert(var_name.as_str(), color_idx % colors.len());
+        for &var_idx in &selected_ids {
+            let has_data = self.runs.iter().any(|run| run.error_series(var_idx).is_some() || run.value_series(var_idx).is_some());
+            if has_data {
+                variable_color_map.insert(var_idx, color_idx % colors.len());
                 color_idx += 1;
             }
         }
-        // Variable selection and options in scrollable area
-        egui::ScrollArea::vertical()
-            .max_height(250.0)
-            .show(ui, |ui| {
-                // Calculate optimal number of columns based on available width
-                // Estimate column width: checkbox + text + padding (~200px per column)
-                let available_width = ui.available_width();
-                let estimated_column_width = 200.0;
-                let columns_count = (available_width / estimated_column_width) as usize;
-                let vars_per_column = filtered_vars.len().div_ceil(columns_count);
-                
-                ui.horizontal_top(|ui| {
-                    for col_idx in 0..columns_count {
-                        let start = col_idx * vars_per_column;
-                        let end = ((col_idx + 1) * vars_per_column).min(filtered_vars.len());
+        // Variable ids follow name order, other orders need the metrics
+        let mut grid_vars = filtered_vars.clone();
+        if self.grid_sort != GridSort::default()
+            && let Some(run) = self.convergence_run() {
+            sort_variables(run, &mut grid_vars, self.convergence_tolerance.0, self.grid_sort);
+        }
+        
+        // Variable selection in a scrollable area, with the convergence summary beside it
+        ui.horizontal_top(|ui| {
+            let grid_width = if self.runs.is_empty() { ui.available_width() } else { ui.available_width() * 0.55 };
+            ui.allocate_ui(egui::vec2(grid_width, 250.0), |ui| {
+                ui.set_max_width(grid_width);
+                egui::ScrollArea::vertical()
+                    .max_height(250.0)
+                    .show(ui, |ui| {
+                        if self.variable_view == VariableView::Tree {
+                            let tree = GroupNode::build(&self.variable_names, &filtered_vars, &self.group_separators);
+                            let color_of = |var: usize| variable_color_map.get(&var).map(|&color_index| colors[color_index]);
+                            show_group_tree(ui, &tree, &self.variable_names, &mut self.selected_vars, &color_of);
+                            return;
+                        }
                         
-                        if start >= filtered_vars.len() {
-                            break;
-                        }                        
-                        ui.vertical(|ui| {
-                            for var_name in &filtered_vars[start..end] {
-                                if let Some(var_index) = self.variable_names.iter().position(|x| x == var_name) {
-                                    if var_index >= self.selected_vars.len() {
-                                        continue;
-                                    }
+                        // Calculate optimal number of columns based on available width
+                        // Estimate column width: checkbox + text + padding (~200px per column)
+                        let available_width = ui.available_width();
+                        let estimated_column_width = 200.0;
+                        let columns_count = ((available_width / estimated_column_width) as usize).max(1);
+                        let vars_per_column = grid_vars.len().div_ceil(columns_count);
+                
+                        ui.horizontal_top(|ui| {
+                            for col_idx in 0..columns_count {
+                                let start = col_idx * vars_per_column;
+                                let end = ((col_idx + 1) * vars_per_column).min(grid_vars.len());
+                        
+                                if start >= grid_vars.len() {
+                                    break;
+                                }                        
+                                ui.vertical(|ui| {
+                                    for &var_index in &grid_vars[start..end] {
+                                        let var_name = &self.variable_names[var_index];
+                                        if var_index < self.selected_vars.len() {
                                     
-                                    ui.group(|ui| {
-                                        ui.vertical(|ui| {
-                                            // Main checkbox to select the variable
-                                            let mut selected = self.selected_vars[var_index];
+                                            ui.group(|ui| {
+                                                ui.vertical(|ui| {
+                                                    // Main checkbox to select the variable
+                                                    let mut selected = self.selected_vars[var_index];
                                             
-                                            // Style the checkbox based on selection and color mapping
-                                            if selected 
-                                                && let Some(&color_index) = variable_color_map.get(var_name.as_str()){
-                                                let graph_color = colors[color_index];
-                                                
-                                                // Create a custom checkbox style with the graph color
-                                                let mut checkbox_style = ui.style().visuals.widgets.inactive;
-                                                checkbox_style.bg_fill = graph_color;
-                                                checkbox_style.bg_stroke = egui::Stroke::new(1.0, graph_color.gamma_multiply(0.8));
-                                                
-                                                let mut active_style = ui.style().visuals.widgets.active;
-                                                active_style.bg_fill = graph_color;
-                                                active_style.bg_stroke = egui::Stroke::new(2.0, graph_color.gamma_multiply(0.8));
-                                                
-                                                ui.style_mut().visuals.widgets.inactive = checkbox_style;
-                                                ui.style_mut().visuals.widgets.active = active_style;
-                                            }
+                                                    // Style the checkbox based on selection and color mapping
+                                                    if selected 
+                                                        && let Some(&color_index) = variable_color_map.get(&var_index){
+                                                        tint_checkbox(ui, colors[color_index]);
+                                                    }
                                             
-                                            if ui.checkbox(&mut selected, format!("📈 {var_name}")).changed() {
-                                                self.selected_vars[var_index] = selected;
-                                            }
-                                        });
-                                    });
+                                                    if ui.checkbox(&mut selected, format!("📈 {var_name}")).changed() {
+                                                        self.selected_vars[var_index] = selected;
+                                                    }
+                                                });
+                                            });
                                     
-                                    ui.add_space(2.0); // Small spacing between variables
+                                            ui.add_space(2.0); // Small spacing between variables
+                                        }
+                                    }
+                                });
+                        
+                                // Add column separator
+                                if col_idx < columns_count - 1 && end < grid_vars.len() {
+                                    ui.separator();
                                 }
                             }
                         });
-                        
-                        // Add column separator
-                        if col_idx < columns_count - 1 && end < filtered_vars.len() {
-                            ui.separator();
-                        }
-                    }
-                });
+                    });
             });
+            
+            if !self.runs.is_empty() {
+                ui.separator();
+                ui.vertical(|ui| self.show_convergence_summary(ui, &filtered_vars));
+            }
+        });
+        let selected_variables: Vec<(usize, &String)> = selected_ids
+            .iter()
+            .map(|&var_idx| (var_idx, &self.variable_names[var_idx]))
+            .collect();
         
         ui.separator();
         
@@ -611,10 +1612,9 @@ impl CalibrationApp {
             ui.label(format!("📊 Selected: {selected_count} variables"));
             
             if ui.button("✅ Select All Filtered").clicked() {
-                for var_name in &filtered_vars {
-                    if let Some(var_index) = self.variable_names.iter().position(|x| x == var_name) 
-                        && var_index < self.selected_vars.len(){
-                            self.selected_vars[var_index] = true;
+                for &var_index in &filtered_vars {
+                    if var_index < self.selected_vars.len() {
+                        self.selected_vars[var_index] = true;
                     }
                 }
             }
@@ -632,176 +1632,905 @@ impl CalibrationApp {
         if selection_changed {
             self.prev_selected_vars = self.selected_vars.clone();
         }
+
+        // The diff view takes the place of the overlaid plots
+        if self.view_mode == ViewMode::Diff
+            && let Some((baseline, candidate)) = self.diff_runs() {
+            let toggled = self.show_diff_section(ui, baseline, candidate, &selected_variables, &variable_color_map, selection_changed);
+            if let Some(var_index) = toggled {
+                self.selected_vars[var_index] = !self.selected_vars[var_index];
+            }
+            return;
+        }
         
-        if !selected_variables.is_empty() {
+        // Groups a fan chart can summarize instead of the selection, only built in fan mode
+        let build_groups = || GroupNode::build(&self.variable_names, &(0..self.variable_names.len()).collect::<Vec<_>>(), &self.group_separators);
+        let mut groups = None;
+        let (prev_plot_mode, prev_fan_group) = (self.plot_mode, self.fan_group.clone());
+
+        if !selected_variables.is_empty() || self.plot_mode == PlotMode::Fan {
             ui.separator();
-            ui.label(RichText::new("📈 Selected Variables Plots").heading());
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("📈 Selected Variables Plots").heading());
+                
+                ui.separator();
+                for mode in PlotMode::ALL {
+                    ui.selectable_value(&mut self.plot_mode, mode, mode.label());
+                }
+                if self.plot_mode == PlotMode::Fan {
+                    let groups = groups.get_or_insert_with(build_groups);
+                    ui.label("across");
+                    let group_label = |group: &GroupNode| format!("{} ({})", group.path, group.vars.len());
+                    let selected_text = match self.fan_group.as_deref().and_then(|path| groups.find(path)) {
+                        Some(group) => group_label(group),
+                        None => format!("Filtered variables ({})", filtered_vars.len()),
+                    };
+                    egui::ComboBox::from_id_salt("fan_group")
+                        .width(180.0)
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.fan_group, None, format!("Filtered variables ({})", filtered_vars.len()));
+                            for group in groups.descendants() {
+                                ui.selectable_value(&mut self.fan_group, Some(group.path.clone()), group_label(group));
+                            }
+                        })
+                        .response
+                        .on_hover_text("The median of these variables per iteration, inside their 10–90% and min–max bands");
+                }
+                
+                ui.separator();
+                ui.label("Error:");
+                egui::ComboBox::from_id_salt("error_mode")
+                    .width(80.0)
+                    .selected_text(self.error_mode.label())
+                    .show_ui(ui, |ui| {
+                        for mode in ErrorMode::ALL {
+                            ui.selectable_value(&mut self.error_mode, mode, mode.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Relative errors are divided by the variable's Target series if the report has one, otherwise by its Value");
+                ui.label("Scale:");
+                egui::ComboBox::from_id_salt("error_axis_scale")
+                    .width(80.0)
+                    .selected_text(self.error_axis.scale.label())
+                    .show_ui(ui, |ui| {
+                        for scale in YScale::ALL {
+                            ui.selectable_value(&mut self.error_axis.scale, scale, scale.label());
+                        }
+                    });
+                if self.error_axis.scale == YScale::SymLog {
+                    ui.label("linear within ±");
+                    ui.add(egui::DragValue::new(&mut self.error_axis.linear_width).speed(1e-7).range(1e-12..=f64::MAX))
+                        .on_hover_text("Errors smaller than this are drawn on a linear scale around zero");
+                }
+                
+                ui.separator();
+                ui.label("Steps:");
+                ui.checkbox(&mut self.delta_plots.value, "ΔValue")
+                    .on_hover_text("Plot the change of each Value from the previous iteration");
+                ui.checkbox(&mut self.delta_plots.error, "ΔError")
+                    .on_hover_text("Plot the change of each Error from the previous iteration, in the chosen error mode");
+            });
             ui.separator();
+        }
+        
+        // A group that no longer exists, e.g. after changing the separators, falls back to the filter
+        let plot_variables: Vec<(usize, &String)> = match self.plot_mode {
+            PlotMode::Lines => selected_variables.clone(),
+            PlotMode::Fan => self.fan_group
+                .as_deref()
+                .and_then(|path| groups.get_or_insert_with(build_groups).find(path))
+                .map_or(&filtered_vars[..], |group| &group.vars)
+                .iter()
+                .map(|&var_idx| (var_idx, &self.variable_names[var_idx]))
+                .collect(),
+        };
+        if !plot_variables.is_empty() {
+            // Refit the Error plot when its mode or scale changes
+            let axis_changed = self.error_axis != self.prev_error_axis || self.error_mode != self.prev_error_mode;
+            self.prev_error_axis = self.error_axis;
+            self.prev_error_mode = self.error_mode;
+            let fan_changed = self.plot_mode != prev_plot_mode || self.fan_group != prev_fan_group;
             
-            // Check if we have any error or value data to show
-            let has_error_data = selected_variables.iter().any(|(_, var_name)| {
-                self.has_error_column(var_name)
-            });
+            // Sorting every row of a fan touches every sample, only do it when its inputs change
+            if self.plot_mode == PlotMode::Fan {
+                let mut rebuilt = Vec::new();
+                for kind in self.plot_kinds() {
+                    let key = FanKey {
+                        runs: self.runs.iter().map(|run| (run.id, run.generation)).collect(),
+                        vars: plot_variables.iter().map(|&(var, _)| var).collect(),
+                        mode: self.error_mode,
+                        smoothing: self.smoothing(&kind),
+                    };
+                    if self.fans.get(&kind).is_none_or(|(built, _)| *built != key) {
+                        let series = self.plot_series(&plot_variables, &kind, &colors);
+                        let fans = self.fan_bands(&series).into_iter().map(|(run, fan)| (run.id, fan)).collect();
+                        rebuilt.push((kind, (key, fans)));
+                    }
+                }
+                self.fans.extend(rebuilt);
+            }
             
-            let has_value_data = selected_variables.iter().any(|(_, var_name)| {
-                self.has_value_column(var_name)
-            });
+            // Lines of every run for the selected variables, or only the fans built from them, one
+            // plot per series kind with data
+            let plots: Vec<(String, Vec<PlotSeries>)> = self.plot_kinds()
+                .into_iter()
+                .filter_map(|kind| match self.plot_mode {
+                    PlotMode::Lines => {
+                        let series = self.plot_series(&plot_variables, &kind, &colors);
+                        (!series.is_empty()).then_some((kind, series))
+                    }
+                    PlotMode::Fan => self.fans.get(&kind).is_some_and(|(_, fans)| !fans.is_empty()).then_some((kind, Vec::new())),
+                })
+                .collect();
             
-            // Show plots side by side
+            // Show plots side by side, two to a row
+            let mut smoothing_changes = Vec::new();
+            for row in plots.chunks(2) {
+                ui.horizontal(|ui| {
+                    let total_width = ui.available_width();
+                    let plot_width = (total_width - 40.0) * 0.5;
+                    ui.add_space(5.0); // Extra spacing between plots
+                    
+                    for (plot_idx, (kind, series)) in row.iter().enumerate() {
+                        // Add spacing between plots
+                        if plot_idx > 0 {
+                            ui.add_space(2.0); // Extra spacing between plots
+                            ui.separator();
+                            ui.add_space(2.0); // Extra spacing between plots
+                        }
+                        let shows_errors = delta::base_kind(kind).unwrap_or(kind) == schema::ERROR;
+                        let reset_view = selection_changed || fan_changed || (axis_changed && shows_errors);
+                        if let Some(smoothing) = self.show_series_plot(ui, kind, series, &plot_variables, plot_width, reset_view) {
+                            smoothing_changes.push((kind.to_string(), smoothing));
+                        }
+                    }
+                });
+            }
+            if !smoothing_changes.is_empty() {
+                self.smoothing.extend(smoothing_changes);
+                ui.ctx().request_repaint();
+            }
+        }
+        
+        if self.runs.iter().any(|run| run.data.kinds.iter().any(|kind| kind == schema::ERROR)) {
+            let aggregate_vars = match self.aggregate_view.scope {
+                AggregateScope::All => (0..self.variable_names.len()).collect(),
+                AggregateScope::Filtered => filtered_vars.clone(),
+                AggregateScope::Selected => selected_ids.clone(),
+            };
+            self.show_forecast_section(ui, &selected_ids, &aggregate_vars);
+            if !selected_ids.is_empty() {
+                self.show_phase_section(ui, &selected_ids);
+            }
+            self.show_heatmap_section(ui, &filtered_vars);
+            self.show_aggregate_section(ui, &aggregate_vars);
+        }
+    }
+    
+    /// Plot of one series kind for the selected variables, with its smoothing controls and a context
+    /// menu to export it. Returns the plot's new smoothing if it was changed.
+    fn show_series_plot(&self, ui: &mut Ui, kind: &str, plot_series: &[PlotSeries], selected_variables: &[(usize, &String)], plot_width: f32, reset_view: bool) -> Option<Smoothing> {
+        let mut smoothing = self.smoothing(kind);
+        ui.vertical(|ui| {
+            ui.add_space(5.0); // Increased top padding
+            let icon = match kind {
+                "Error" => "🔴",
+                "Value" => "🔵",
+                _ if delta::base_kind(kind).is_some() => "🔺",
+                _ => "🟣",
+            };
             ui.horizontal(|ui| {
-                let total_width = ui.available_width();
-                let plot_width = (total_width - 40.0) * 0.5;
-                ui.add_space(5.0); // Extra spacing between plots
-                
-                // Use shared plot memory for legend synchronization
-                let plot_memory_id = egui::Id::new("shared_plot_memory");
-                
-                // Error plot (left side)
-                if has_error_data {
-                    ui.vertical(|ui| {
-                        ui.add_space(5.0); // Increased top padding
-                        ui.label(RichText::new("🔴 Error").strong());
-                        ui.add_space(2.0); // Increased spacing after label
-                        
-                        let mut error_plot = Plot::new("error_plot")
-                            .view_aspect(2.0) // Increased aspect ratio for more horizontal space
-                            .height(450.0) // Increased height
-                            .width(plot_width) // Reduced width to add margins
-                            .legend(egui_plot::Legend::default())
-                            .x_axis_label("Iteration")
-                            .y_axis_label("Error")
-                            .link_cursor(plot_memory_id, true)
-                            ; // Link cursor and shared state
-                        
-                        // Reset view if selection changed
-                        if selection_changed {
-                            error_plot = error_plot.auto_bounds(egui::Vec2b::new(true, true)).reset();
+                ui.label(RichText::new(format!("{icon} {kind}")).strong());
+                ui.separator();
+                ui.label("Smoothing:");
+                egui::ComboBox::from_id_salt(format!("{kind}_smoothing"))
+                    .width(110.0)
+                    .selected_text(smoothing.kind.label())
+                    .show_ui(ui, |ui| {
+                        for smoothing_kind in SmoothingKind::ALL {
+                            ui.selectable_value(&mut smoothing.kind, smoothing_kind, smoothing_kind.label());
                         }
-                        
-                        let error_plot_response = error_plot.show(ui, |plot_ui| {
-                                let mut plot_idx = 0;
+                    });
+                match smoothing.kind {
+                    SmoothingKind::None => {}
+                    SmoothingKind::MovingAverage => {
+                        ui.add(egui::DragValue::new(&mut smoothing.window).range(1..=10_000).suffix(" iterations"))
+                            .on_hover_text("Number of iterations averaged, ending at each iteration");
+                    }
+                    SmoothingKind::Ewma => {
+                        ui.add(egui::DragValue::new(&mut smoothing.alpha).speed(0.01).range(0.01..=1.0).prefix("α = "))
+                            .on_hover_text("Weight of the newest sample, smaller values smooth more");
+                    }
+                }
+            });
+            ui.add_space(2.0); // Increased spacing after label
+            
+            let mut plot = Plot::new(format!("{}_plot", kind.to_lowercase()))
+                .view_aspect(2.0) // Increased aspect ratio for more horizontal space
+                .height(450.0) // Increased height
+                .width(plot_width) // Reduced width to add margins
+                .legend(egui_plot::Legend::default())
+                .x_axis_label("Iteration")
+                .y_axis_label(self.axis_label(kind))
+                .link_cursor(egui::Id::new("shared_plot_memory"), true); // Link cursor and shared state
+            
+            // Log and symlog axes plot transformed samples, ticks and hover labels show the real values
+            let axis = if kind == schema::ERROR { self.error_axis } else { YAxis::default() };
+            if !axis.is_linear() {
+                plot = plot
+                    .y_axis_label(format!("{} ({})", self.axis_label(kind), axis.scale.label().to_lowercase()))
+                    .y_grid_spacer(move |input| {
+                        axis.ticks(input.bounds.0, input.bounds.1)
+                            .into_iter()
+                            .map(|tick| egui_plot::GridMark { value: tick.coord, step_size: tick.step })
+                            .collect()
+                    })
+                    .y_axis_formatter(move |mark, _range| format_tick(axis.inverse(mark.value)))
+                    .label_formatter(move |name, point| {
+                        let value = format_tick(axis.inverse(point.y));
+                        if name.is_empty() {
+                            format!("x = {:.0}\ny = {value}", point.x)
+                        } else {
+                            format!("{name}\nx = {:.0}\ny = {value}", point.x)
+                        }
+                    });
+            }
+            
+            // Reset view if selection or scale changed
+            if reset_view {
+                plot = plot.auto_bounds(egui::Vec2b::new(true, true)).reset();
+            }
+            
+            // In fan mode the series only feed the fans
+            let (fans, line_series) = match self.plot_mode {
+                PlotMode::Lines => (Vec::new(), plot_series),
+                PlotMode::Fan => {
+                    let fans: Vec<(&Run, FanBands)> = self.fans
+                        .get(kind)
+                        .into_iter()
+                        .flat_map(|(_, fans)| fans)
+                        .filter_map(|(id, fan)| Some((self.runs.iter().find(|run| run.id == *id)?, fan.map(|val| axis.transform(val)))))
+                        .collect();
+                    (fans, &[][..])
+                }
+            };
+            
+            let plot_response = plot.show(ui, |plot_ui| {
+                for (run, fan) in &fans {
+                    let color = self.run_color(run);
+                    let legend = |label: &str| if self.runs.len() > 1 { format!("{label} [{}]", run.label) } else { label.to_string() };
+                    // Polygons are only filled when convex, so each band is drawn as a strip of
+                    // quads, at most FAN_QUADS of them across the run
+                    let step = run.data.len().div_ceil(FAN_QUADS).max(1);
+                    for ((label, lower, upper), opacity) in fan.bands().into_iter().zip([0.15, 0.3]) {
+                        let name = legend(label);
+                        for span in fan::spans(lower, upper) {
+                            let rows: Vec<usize> = span.clone().step_by(step).chain(std::iter::once(span.end - 1)).collect();
+                            for pair in rows.windows(2).filter(|pair| pair[0] != pair[1]) {
+                                let [first, second] = [pair[0], pair[1]].map(|row| run.data.iterations[row] as f64);
+                                let quad = vec![[first, lower[pair[0]]], [second, lower[pair[1]]], [second, upper[pair[1]]], [first, upper[pair[0]]]];
+                                plot_ui.polygon(
+                                    egui_plot::Polygon::new(name.as_str(), PlotPoints::from(quad))
+                                        .fill_color(color.gamma_multiply(opacity))
+                                        .stroke(egui::Stroke::NONE),
+                                );
+                            }
+                        }
+                    }
+                    
+                    let name = legend("Median");
+                    for segment in run.data.segments(&fan.median) {
+                        if segment.len() == 1 {
+                            plot_ui.points(Points::new(name.as_str(), PlotPoints::from(segment)).color(color).radius(2.5));
+                        } else {
+                            plot_ui.line(Line::new(name.as_str(), PlotPoints::from(segment)).color(color).style(run.line_style.plot_style()).width(2.0));
+                        }
+                    }
+                }
+                
+                for series in line_series {
+                    // A smoothed series is drawn thick over its faded raw samples
+                    let raw = if series.smoothed.is_some() { (series.color.gamma_multiply(0.3), 1.0) } else { (series.color, 2.0) };
+                    let lines = [Some((&series.samples[..], raw)), series.smoothed.as_deref().map(|smoothed| (smoothed, (series.color, 3.0)))];
+                    for (samples, (color, width)) in lines.into_iter().flatten() {
+                        let samples: Vec<f64> = samples.iter().map(|&val| axis.transform(val)).collect();
+                        // Lines sharing a name are merged into one legend entry
+                        for segment in series.run.data.segments(&samples) {
+                            if segment.len() == 1 {
+                                // An isolated sample has no line to draw, mark it instead
+                                plot_ui.points(Points::new(series.legend.as_str(), PlotPoints::from(segment)).color(color).radius(width + 0.5));
+                            } else {
+                                let line = Line::new(series.legend.as_str(), PlotPoints::from(segment))
+                                    .color(color)
+                                    .style(series.run.line_style.plot_style())
+                                    .width(width);
                                 
-                                for (_, var_name) in &selected_variables {
-                                    if self.has_error_column(var_name) && let Some(error_col) = self.get_error_column_name(var_name){
-                                        let points: PlotPoints = self.records
-                                            .iter()
-                                            .filter_map(|r| {
-                                                r.data.get(&error_col).map(|&val| [r.iteration as f64, val])
-                                            })
-                                            .collect();
-                                        
-                                        let line = Line::new(var_name.as_str(), points)
-                                            .color(colors[plot_idx % colors.len()])
-                                            .width(2.0);
-                                        
-                                        plot_ui.line(line);
-                                        plot_idx += 1;
-                                    }
+                                plot_ui.line(line);
+                            }
+                        }
+                    }
+                    
+                    if kind == schema::ERROR
+                        && let Some(points) = self.error_projection(series, axis) {
+                        plot_ui.line(
+                            Line::new(series.legend.as_str(), PlotPoints::from(points))
+                                .color(series.color)
+                                .style(egui_plot::LineStyle::dashed_dense())
+                                .width(1.5),
+                        );
+                    }
+                }
+            });
+            
+            // Handle right-click context menu
+            plot_response.response.context_menu(|ui| {
+                if ui.button("💾 Save as CSV").clicked() {
+                    if let Err(e) = self.save_plot_csv(selected_variables, kind) {
+                        eprintln!("Failed to save CSV: {e}");
+                    }
+                    ui.close();
+                }
+                if ui.button("🗄 Save as Parquet").clicked() {
+                    if let Err(e) = self.save_plot_parquet(selected_variables, kind) {
+                        eprintln!("Failed to save Parquet: {e}");
+                    }
+                    ui.close();
+                }
+                if ui.button("📸 Save as Image").clicked() {
+                    if let Err(e) = self.save_plot_image(selected_variables, kind, &PLOT_COLORS, Some(plot_response.transform.bounds()), ui.ctx()) {
+                        eprintln!("Failed to save image: {e}");
+                    }
+                    ui.close();
+                }
+            });
+        });
+        (smoothing != self.smoothing(kind)).then_some(smoothing)
+    }
+    
+    /// RMSE, mean and max |error| and SSE per iteration over `vars`, one line per metric and run
+    fn show_aggregate_section(&mut self, ui: &mut Ui, vars: &[usize]) {
+        ui.separator();
+        egui::CollapsingHeader::new(RichText::new("📐 Aggregate Objective").heading())
+            .id_salt("aggregate")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("aggregate_scope")
+                        .selected_text(self.aggregate_view.scope.label())
+                        .show_ui(ui, |ui| {
+                            for scope in AggregateScope::ALL {
+                                ui.selectable_value(&mut self.aggregate_view.scope, scope, scope.label());
+                            }
+                        });
+                    ui.label(format!("({} variables)", vars.len()));
+                    
+                    ui.separator();
+                    for metric in AggregateMetric::ALL {
+                        let shown = self.aggregate_view.metrics.contains(&metric);
+                        if ui.selectable_label(shown, metric.label()).clicked() {
+                            if shown {
+                                self.aggregate_view.metrics.retain(|&m| m != metric);
+                            } else {
+                                self.aggregate_view.metrics.push(metric);
+                            }
+                        }
+                    }
+                });
+                
+                let plot = Plot::new("aggregate_plot")
+                    .height(300.0)
+                    .legend(egui_plot::Legend::default())
+                    .x_axis_label("Iteration")
+                    .y_axis_label("Aggregate Error")
+                    .link_cursor(egui::Id::new("shared_plot_memory"), true);
+                plot.show(ui, |plot_ui| {
+                    for run in &self.runs {
+                        let aggregate = ErrorAggregate::new(run, vars);
+                        for (metric_idx, metric) in AggregateMetric::ALL.into_iter().enumerate() {
+                            if !self.aggregate_view.metrics.contains(&metric) {
+                                continue;
+                            }
+                            let name = if self.runs.len() > 1 {
+                                format!("{} [{}]", metric.label(), run.label)
+                            } else {
+                                metric.label().to_string()
+                            };
+                            for segment in run.data.segments(aggregate.series(metric)) {
+                                if segment.len() == 1 {
+                                    plot_ui.points(Points::new(name.as_str(), PlotPoints::from(segment)).color(PLOT_COLORS[metric_idx]).radius(2.5));
+                                } else {
+                                    plot_ui.line(
+                                        Line::new(name.as_str(), PlotPoints::from(segment))
+                                            .color(PLOT_COLORS[metric_idx])
+                                            .style(run.line_style.plot_style())
+                                            .width(2.0),
+                                    );
                                 }
-                            });
-                        
-                        // Handle right-click context menu for error plot
-                        error_plot_response.response.context_menu(|ui| {
-                            if ui.button("💾 Save as CSV").clicked() {
-                                if let Err(e) = self.save_plot_csv(&selected_variables, "Error") {
-                                    eprintln!("Failed to save CSV: {e}");
+                            }
+                            if self.forecast.enabled
+                                && let Some(fit) = DecayFit::new(&self.forecast, &run.data.iterations, aggregate.series(metric)) {
+                                let points = fit.curve(fit.projection_end(self.convergence_tolerance.0));
+                                plot_ui.line(
+                                    Line::new(name.as_str(), PlotPoints::from(points))
+                                        .color(PLOT_COLORS[metric_idx])
+                                        .style(egui_plot::LineStyle::dashed_dense())
+                                        .width(1.5),
+                                );
+                            }
+                        }
+                    }
+                });
+            });
+    }
+    
+    /// Settings of the decay fits, and for each selected variable and aggregate metric the fitted
+    /// rate and the iteration it is projected to reach the tolerance at
+    fn show_forecast_section(&mut self, ui: &mut Ui, selected_ids: &[usize], aggregate_vars: &[usize]) {
+        let tolerance = self.convergence_tolerance.0;
+        
+        ui.separator();
+        egui::CollapsingHeader::new(RichText::new("⏱ Convergence Forecast").heading())
+            .id_salt("forecast")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.forecast.enabled, "Project fits")
+                        .on_hover_text("Draw the fitted decays as dashed lines on the Error and aggregate plots");
+                    egui::ComboBox::from_id_salt("forecast_model")
+                        .selected_text(self.forecast.model.label())
+                        .show_ui(ui, |ui| {
+                            for model in DecayModel::ALL {
+                                ui.selectable_value(&mut self.forecast.model, model, model.label());
+                            }
+                        });
+                    ui.label("fitted to the last");
+                    ui.add(egui::DragValue::new(&mut self.forecast.window).range(0..=1_000_000).suffix(" iterations"))
+                        .on_hover_text("0 fits the whole history");
+                    ui.label(format!("· tolerance {tolerance}"));
+                });
+                
+                // One row per fitted series: name, run, rate, R², projected iteration
+                let mut rows: Vec<(String, &Run, Option<DecayFit>)> = Vec::new();
+                for &var in selected_ids {
+                    for run in self.runs.iter().filter(|run| run.error_series(var).is_some()) {
+                        rows.push((self.variable_names[var].clone(), run, self.fit_errors(run, var)));
+                    }
+                }
+                for run in &self.runs {
+                    let aggregate = ErrorAggregate::new(run, aggregate_vars);
+                    for &metric in &self.aggregate_view.metrics {
+                        let fit = DecayFit::new(&self.forecast, &run.data.iterations, aggregate.series(metric));
+                        rows.push((format!("{} ({})", metric.label(), self.aggregate_view.scope.label().to_lowercase()), run, fit));
+                    }
+                }
+                
+                egui::ScrollArea::vertical()
+                    .id_salt("forecast_table")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("forecast_grid").striped(true).show(ui, |ui| {
+                            let multi_run = self.runs.len() > 1;
+                            for header in ["Series", "Run", "Rate", "R²", "Tolerance Reached"] {
+                                if header != "Run" || multi_run {
+                                    ui.label(RichText::new(header).strong());
                                 }
-                                ui.close();
                             }
-                            if ui.button("📸 Save as Image").clicked() {
-                                if let Err(e) = self.save_plot_image(&selected_variables, "Error", &colors, Some(error_plot_response.transform.bounds()), ctx) {
-                                    eprintln!("Failed to save image: {e}");
+                            ui.end_row();
+                            
+                            for (name, run, fit) in &rows {
+                                ui.label(name.as_str());
+                                if multi_run {
+                                    ui.label(run.label.as_str());
+                                }
+                                let Some(fit) = fit else {
+                                    ui.colored_label(Color32::GRAY, "too few samples");
+                                    ui.end_row();
+                                    continue;
+                                };
+                                ui.label(fit.describe_rate());
+                                ui.label(format!("{:.3}", fit.r_squared));
+                                match fit.iteration_reaching(tolerance) {
+                                    Some(reach) if reach <= fit.last as f64 => {
+                                        ui.colored_label(Color32::from_rgb(0, 200, 0), "already within");
+                                    }
+                                    Some(reach) => {
+                                        let reach = reach.ceil();
+                                        ui.label(format!("≈ iteration {reach:.0} ({:.0} more)", reach - fit.last as f64));
+                                    }
+                                    None => {
+                                        ui.colored_label(Color32::from_rgb(220, 120, 0), "not decaying");
+                                    }
                                 }
-                                ui.close();
+                                ui.end_row();
                             }
                         });
                     });
+            });
+    }
+    
+    /// Error against Value of one of `selected_ids`, connected in iteration order and coloured by
+    /// iteration, one trajectory per run
+    fn show_phase_section(&mut self, ui: &mut Ui, selected_ids: &[usize]) {
+        let var = self.phase_var.filter(|var| selected_ids.contains(var)).unwrap_or(selected_ids[0]);
+        let trajectories: Vec<(&Run, PhaseTrajectory)> = self.runs
+            .iter()
+            .filter_map(|run| {
+                let errors = self.displayed_errors(run, var)?;
+                Some((run, PhaseTrajectory::new(&run.data.iterations, run.value_series(var)?, &errors)))
+            })
+            .collect();
+        // The colour ramp spans the iterations of every run so the runs can be compared
+        let spans: Vec<_> = trajectories.iter().filter_map(|(_, t)| t.span()).collect();
+        let first = spans.iter().map(|span| *span.start()).min().unwrap_or(0);
+        let last = spans.iter().map(|span| *span.end()).max().unwrap_or(0);
+        let mut picked = var;
+        
+        ui.separator();
+        egui::CollapsingHeader::new(RichText::new("🌀 Phase Plot").heading())
+            .id_salt("phase")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("phase_var")
+                        .selected_text(self.variable_names[var].as_str())
+                        .show_ui(ui, |ui| {
+                            for &id in selected_ids {
+                                ui.selectable_value(&mut picked, id, self.variable_names[id].as_str());
+                            }
+                        });
+                    
+                    ui.separator();
+                    color_ramp_legend(ui, format!("Iteration {first}"), last.to_string(), iteration_color);
+                });
+                
+                if trajectories.is_empty() {
+                    ui.colored_label(Color32::GRAY, "This variable needs both an Error and a Value column");
+                    return;
                 }
                 
-                // Add spacing between plots
-                if has_error_data && has_value_data {
-                    ui.add_space(2.0); // Extra spacing between plots
-                    ui.separator();
-                    ui.add_space(2.0); // Extra spacing between plots
-                }
-                
-                // Value plot (right side)
-                if has_value_data {
-                    ui.vertical(|ui| {
-                        ui.add_space(5.0); // Increased top padding
-                        ui.label(RichText::new("🔵 Value").strong());
-                        ui.add_space(2.0); // Increased spacing after label
-                        let legend = egui_plot::Legend::default();
-                        let mut value_plot = Plot::new("value_plot")
-                            .view_aspect(2.0) // Increased aspect ratio for more horizontal space
-                            .height(450.0) // Increased height
-                            .width(plot_width) // Reduced width to add margins
-                            .legend(legend)
-                            .x_axis_label("Iteration")
-                            .y_axis_label("Value")
-                            .link_cursor(plot_memory_id, true); // Link to the same shared state
-                        
-                        // Reset view if selection changed
-                        if selection_changed {
-                            value_plot = value_plot.auto_bounds(egui::Vec2b::new(true, true)).reset();
+                let error_label = self.axis_label(schema::ERROR);
+                let plot = Plot::new("phase_plot")
+                    .height(400.0)
+                    .legend(egui_plot::Legend::default())
+                    .x_axis_label("Value")
+                    .y_axis_label(error_label.as_str())
+                    .label_formatter(move |name, point| {
+                        let prefix = if name.is_empty() { String::new() } else { format!("{name}\n") };
+                        format!("{prefix}Value = {:.4}\n{error_label} = {:.4}", point.x, point.y)
+                    });
+                plot.show(ui, |plot_ui| {
+                    // Zero error is where the trajectory should end up
+                    plot_ui.hline(egui_plot::HLine::new("", 0.0).color(Color32::GRAY).width(1.0));
+                    for (run, trajectory) in &trajectories {
+                        let name = if self.runs.len() > 1 { run.label.as_str() } else { self.variable_names[var].as_str() };
+                        for (points, t) in trajectory.bands(&(first..=last)) {
+                            plot_ui.line(
+                                Line::new(name, PlotPoints::from(points.to_vec()))
+                                    .color(iteration_color(t))
+                                    .style(run.line_style.plot_style())
+                                    .width(2.0),
+                            );
                         }
-                        
-                        let value_plot_response = value_plot.show(ui, |plot_ui| {
-                                let mut plot_idx = 0;
-                                
-                                for (_, var_name) in &selected_variables {
-                                    if self.has_value_column(var_name) &&
-                                        let Some(value_col) = self.get_value_column_name(var_name) {
-                                            let points: PlotPoints = self.records
-                                                .iter()
-                                                .filter_map(|r| {
-                                                    r.data.get(&value_col).map(|&val| [r.iteration as f64, val])
-                                                })
-                                                .collect();
-                                            
-                                            let line = Line::new(var_name.as_str(), points)
-                                                .color(colors[plot_idx % colors.len()])
-                                                .width(2.0);
-                                            
-                                            plot_ui.line(line);
-                                            plot_idx += 1;
-                                        }
+                        // Mark where the trajectory starts and ends
+                        for (idx, shape) in [(0, egui_plot::MarkerShape::Circle), (trajectory.points.len().saturating_sub(1), egui_plot::MarkerShape::Diamond)] {
+                            if let Some(&point) = trajectory.points.get(idx) {
+                                let color = iteration_color(ramp_position(trajectory.iterations[idx], &(first..=last)));
+                                plot_ui.points(Points::new(name, vec![point]).color(color).radius(5.0).shape(shape));
+                            }
+                        }
+                    }
+                });
+            });
+        
+        self.phase_var = Some(picked);
+    }
+    
+    /// Heatmap of the errors of every filtered variable in one run. Hovering a cell names its
+    /// variable and iteration, clicking it adds the variable to the selection.
+    fn show_heatmap_section(&mut self, ui: &mut Ui, filtered_vars: &[usize]) {
+        let Some(run) = self.heatmap_run
+            .and_then(|id| self.runs.iter().find(|run| run.id == id))
+            .or(self.runs.first()) else {
+            return;
+        };
+        let mut clicked = None;
+        let mut picked_run = None;
+        let mut rebuilt = None;
+        
+        ui.separator();
+        egui::CollapsingHeader::new(RichText::new("🌡 Error Heatmap").heading())
+            .id_salt("heatmap")
+            .default_open(false)
+            .show(ui, |ui| {
+                // Building the heatmap touches every sample, only do it when its inputs change
+                let key = HeatmapKey {
+                    run_id: run.id,
+                    generation: run.generation,
+                    vars: filtered_vars.to_vec(),
+                    mode: self.error_mode,
+                    missing: ui.visuals().faint_bg_color,
+                };
+                if self.heatmap.as_ref().is_none_or(|(built, _, _)| *built != key) {
+                    // A texture holds as many rows as the GPU allows
+                    let max_rows = ui.ctx().input(|input| input.max_texture_side);
+                    let vars = &filtered_vars[..filtered_vars.len().min(max_rows)];
+                    let heatmap = Heatmap::new(vars, run.data.len(), |var| self.displayed_errors(run, var));
+                    let texture = ui.ctx().load_texture("error_heatmap", heatmap.image(key.missing), egui::TextureOptions::NEAREST);
+                    rebuilt = Some((key, heatmap, texture));
+                }
+                let Some((_, heatmap, texture)) = rebuilt.as_ref().or(self.heatmap.as_ref()) else { return };
+                
+                ui.horizontal(|ui| {
+                    if self.runs.len() > 1 {
+                        egui::ComboBox::from_id_salt("heatmap_run")
+                            .selected_text(run.label.as_str())
+                            .show_ui(ui, |ui| {
+                                for other in &self.runs {
+                                    if ui.selectable_label(other.id == run.id, &other.label).clicked() {
+                                        picked_run = Some(other.id);
+                                    }
                                 }
                             });
+                    }
+                    ui.label(format!("{} variables × {} iterations", heatmap.vars.len(), run.data.len()));
+                    
+                    ui.separator();
+                    let low = format_tick(-heatmap.limit);
+                    let high = format!("+{}", format_tick(heatmap.limit));
+                    color_ramp_legend(ui, low, high, |t| diverging_color(2.0 * t - 1.0))
+                        .on_hover_text("Errors beyond this are drawn in full colour. The scale covers 98% of the cells.");
+                });
+                
+                if heatmap.vars.is_empty() {
+                    ui.colored_label(Color32::GRAY, "None of the filtered variables has an Error column in this run");
+                    return;
+                }
+                
+                // Rows are at least a pixel high, scroll when they do not fit
+                let row_height = (400.0 / heatmap.vars.len() as f32).clamp(1.0, 14.0);
+                let size = egui::vec2(ui.available_width(), row_height * heatmap.vars.len() as f32);
+                egui::ScrollArea::vertical()
+                    .id_salt("heatmap_scroll")
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        let image = egui::Image::new(egui::load::SizedTexture::new(texture.id(), size)).sense(egui::Sense::click());
+                        let response = ui.add(image);
+                        let Some(pos) = response.hover_pos() else { return };
+                        let offset = pos - response.rect.min;
+                        let row = ((offset.y / row_height) as usize).min(heatmap.vars.len() - 1);
+                        let col = ((offset.x / size.x * heatmap.columns.len() as f32) as usize).min(heatmap.columns.len() - 1);
+                        let var = heatmap.vars[row];
+                        if response.clicked() {
+                            clicked = Some(var);
+                        }
                         
-                        // Handle right-click context menu for value plot
-                        value_plot_response.response.context_menu(|ui| {
-                            if ui.button("💾 Save as CSV").clicked() {
-                                if let Err(e) = self.save_plot_csv(&selected_variables, "Value") {
-                                    eprintln!("Failed to save CSV: {e}");
-                                }
-                                ui.close();
-                            }
-                            if ui.button("📸 Save as Image").clicked() {
-                                if let Err(e) = self.save_plot_image(&selected_variables, "Value", &colors, Some(value_plot_response.transform.bounds()), ctx) {
-                                    eprintln!("Failed to save image: {e}");
-                                }
-                                ui.close();
+                        let bin = &heatmap.columns[col];
+                        let iterations = match (run.data.iterations[bin.start], run.data.iterations[bin.end - 1]) {
+                            (first, last) if first == last => format!("Iteration {first}"),
+                            (first, last) => format!("Iterations {first}–{last}"),
+                        };
+                        let error = heatmap.cell(row, col);
+                        let error = if error.is_nan() { "–".to_string() } else { format_tick(error) };
+                        response.on_hover_text_at_pointer(format!(
+                            "{}\n{iterations}\n{}: {error}",
+                            self.variable_names[var],
+                            self.axis_label(schema::ERROR),
+                        ));
+                    });
+            });
+        
+        if rebuilt.is_some() {
+            self.heatmap = rebuilt;
+        }
+        if picked_run.is_some() {
+            self.heatmap_run = picked_run;
+        }
+        if let Some(var) = clicked {
+            self.selected_vars[var] = true;
+        }
+    }
+    
+    /// Sortable table of when each filtered variable converged and where it ended up. Clicking a
+    /// variable toggles its selection, clicking a header sorts by that column.
+    fn show_convergence_summary(&mut self, ui: &mut Ui, filtered_vars: &[usize]) {
+        let Some(run) = self.convergence_run() else {
+            return;
+        };
+        let rows = summarize(run, filtered_vars, self.convergence_tolerance.0, self.summary_sort);
+        let converged_count = rows.iter().filter(|row| row.converged_at.is_some()).count();
+        let run_id = run.id;
+        
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("🎯 Convergence").strong());
+            ui.label(format!("{converged_count} / {} converged", rows.len()));
+            if self.runs.len() > 1 {
+                let label_of = |id: u64| self.runs.iter().find(|run| run.id == id).map_or("", |run| run.label.as_str());
+                egui::ComboBox::from_id_salt("summary_run")
+                    .selected_text(label_of(run_id))
+                    .show_ui(ui, |ui| {
+                        for run in &self.runs {
+                            if ui.selectable_label(run.id == run_id, &run.label).clicked() {
+                                self.summary_run = Some(run.id);
                             }
-                        });
+                        }
                     });
-                    ui.add_space(100.0); // Extra spacing between plots
-                }
+            }
+        });
+        
+        let format_value = |val: Option<f64>| val.map_or("–".to_string(), |val| format!("{val:.4}"));
+        let mut toggled = None;
+        
+        egui::ScrollArea::both()
+            .id_salt("convergence_table")
+            .max_height(225.0)
+            .show(ui, |ui| {
+                egui::Grid::new("convergence_grid").striped(true).show(ui, |ui| {
+                    for column in SummaryColumn::ALL {
+                        let sorted = self.summary_sort.column == column;
+                        let arrow = match (sorted, self.summary_sort.descending) {
+                            (false, _) => "",
+                            (true, false) => " ⏶",
+                            (true, true) => " ⏷",
+                        };
+                        if ui.selectable_label(sorted, RichText::new(format!("{}{arrow}", column.label())).strong()).clicked() {
+                            self.summary_sort = SummarySort {
+                                column,
+                                descending: sorted && !self.summary_sort.descending,
+                            };
+                        }
+                    }
+                    ui.end_row();
+                    
+                    for row in &rows {
+                        if ui.selectable_label(self.selected_vars[row.var], &self.variable_names[row.var]).clicked() {
+                            toggled = Some(row.var);
+                        }
+                        match row.converged_at {
+                            Some(iteration) => ui.colored_label(Color32::from_rgb(0, 200, 0), iteration.to_string()),
+                            None => ui.colored_label(Color32::GRAY, "not yet"),
+                        };
+                        ui.label(format_value(row.final_error));
+                        ui.label(format_value(row.final_value));
+                        ui.label(format_value(row.max_abs_error));
+                        ui.end_row();
+                    }
+                });
             });
+        
+        if let Some(var) = toggled {
+            self.selected_vars[var] = !self.selected_vars[var];
         }
     }
-
-    fn try_load_file(&mut self) {
-        if let Err(e) = self.load_file(self.file_path.clone()) {
-            self.loading_error = Some(e.to_string());
-            self.file_loaded = false;
+    
+    /// Per-variable deltas between two runs and a plot of their error difference for the selected
+    /// variables. Returns the variable whose name was clicked in the table.
+    fn show_diff_section(&self, ui: &mut Ui, baseline: &Run, candidate: &Run, selected_variables: &[(usize, &String)], variable_color_map: &HashMap<usize, usize>, selection_changed: bool) -> Option<usize> {
+        let diff = RunDiff::new(self.variable_names.len(), baseline, candidate, self.convergence_tolerance.0);
+        let filtered_vars = self.filter_variables();
+        let mut toggled = None;
+        
+        ui.separator();
+        ui.label(RichText::new(format!("🔀 {} vs {}", candidate.label, baseline.label)).heading());
+        if baseline.id == candidate.id {
+            ui.colored_label(Color32::GRAY, "Pick two different runs to compare");
+            return None;
         }
+        ui.small("Δ = candidate − baseline. Click a variable to plot its error difference.");
+        
+        let format_value = |val: Option<f64>| val.map_or("–".to_string(), |val| format!("{val:.4}"));
+        let format_delta = |val: Option<f64>| val.map_or("–".to_string(), |val| format!("{val:+.4}"));
+        let format_iteration = |val: Option<u32>| val.map_or("–".to_string(), |val| val.to_string());
+        
+        egui::ScrollArea::vertical()
+            .id_salt("diff_table")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                egui::Grid::new("diff_grid").striped(true).show(ui, |ui| {
+                    for header in ["Variable", "Final Error", "", "Δ", "Final Value", "", "Δ", "Converged At", "", "Δ"] {
+                        ui.label(RichText::new(header).strong());
+                    }
+                    ui.end_row();
+                    for _ in 0..3 {
+                        ui.label("");
+                        ui.small(baseline.label.as_str());
+                        ui.small(candidate.label.as_str());
+                    }
+                    ui.label("");
+                    ui.end_row();
+                    
+                    for variable in diff.variables.iter().filter(|v| filtered_vars.binary_search(&v.var).is_ok()) {
+                        let var_name = &self.variable_names[variable.var];
+                        if ui.selectable_label(self.selected_vars[variable.var], var_name).clicked() {
+                            toggled = Some(variable.var);
+                        }
+                        
+                        ui.label(format_value(variable.final_error.baseline));
+                        ui.label(format_value(variable.final_error.candidate));
+                        let delta = format_delta(variable.final_error.delta());
+                        match variable.improved() {
+                            Some(true) => ui.colored_label(Color32::from_rgb(0, 200, 0), delta),
+                            Some(false) => ui.colored_label(Color32::from_rgb(220, 50, 50), delta),
+                            None => ui.label(delta),
+                        };
+                        
+                        ui.label(format_value(variable.final_value.baseline));
+                        ui.label(format_value(variable.final_value.candidate));
+                        ui.label(format_delta(variable.final_value.delta()));
+                        
+                        ui.label(format_iteration(variable.converged_at.baseline));
+                        ui.label(format_iteration(variable.converged_at.candidate));
+                        ui.label(variable.converged_at.delta().map_or("–".to_string(), |delta| format!("{delta:+}")));
+                        ui.end_row();
+                    }
+                });
+            });
+        
+        // Variables that cannot be compared
+        for (run, only_in) in [(baseline, &diff.only_in_baseline), (candidate, &diff.only_in_candidate)] {
+            if only_in.is_empty() {
+                continue;
+            }
+            egui::CollapsingHeader::new(format!("Only in {} ({})", run.label, only_in.len()))
+                .id_salt(("diff_only_in", run.id))
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for &var in only_in {
+                            ui.label(self.variable_names[var].as_str());
+                        }
+                    });
+                });
+        }
+        
+        if selected_variables.is_empty() {
+            return toggled;
+        }
+        
+        ui.separator();
+        ui.label(RichText::new("📉 Error Difference").strong());
+        let mut diff_plot = Plot::new("diff_plot")
+            .height(450.0)
+            .legend(egui_plot::Legend::default())
+            .x_axis_label("Iteration")
+            .y_axis_label(format!("Error ({} − {})", candidate.label, baseline.label))
+            .link_cursor(egui::Id::new("shared_plot_memory"), true);
+        if selection_changed {
+            diff_plot = diff_plot.auto_bounds(egui::Vec2b::new(true, true)).reset();
+        }
+        
+        diff_plot.show(ui, |plot_ui| {
+            for &(var_idx, var_name) in selected_variables {
+                let Some(difference) = error_difference(baseline, candidate, var_idx) else { continue };
+                let color = PLOT_COLORS[variable_color_map.get(&var_idx).copied().unwrap_or(0)];
+                for segment in candidate.data.segments(&difference) {
+                    if segment.len() == 1 {
+                        plot_ui.points(Points::new(var_name.as_str(), PlotPoints::from(segment)).color(color).radius(2.5));
+                    } else {
+                        plot_ui.line(Line::new(var_name.as_str(), PlotPoints::from(segment)).color(color).width(2.0));
+                    }
+                }
+            }
+        });
+        
+        toggled
     }
 }
 
 fn main() -> Result<(), eframe::Error> {
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
     
+    // Headless mode: `visualize_calibration_report render <report.csv> ...`
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("render") {
+        cli::attach_console();
+        if let Err(e) = cli::run_render(&args[2..]) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Create a simple icon data (16x16 chart icon)
    
     let options = eframe::NativeOptions {
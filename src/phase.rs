@@ -0,0 +1,111 @@
+// Error-vs-Value trajectories of one variable. Connecting the (value, error) pairs in iteration
+// order shows whether the calibrator overshoots the target and oscillates around it.
+
+use egui::Color32;
+use std::ops::RangeInclusive;
+
+/// How many colour bands a trajectory is split into along the iteration axis
+const COLOR_BANDS: usize = 32;
+
+/// Stops of the colour ramp from the first to the last iteration (viridis)
+const RAMP: [[u8; 3]; 5] = [[68, 1, 84], [59, 82, 139], [33, 145, 140], [94, 201, 98], [253, 231, 37]];
+
+/// Colour of the position `t` (0 = first iteration, 1 = last) on the iteration ramp
+pub fn iteration_color(t: f64) -> Color32 {
+    let scaled = t.clamp(0.0, 1.0) * (RAMP.len() - 1) as f64;
+    let (low, frac) = (scaled.floor() as usize, scaled.fract());
+    let high = (low + 1).min(RAMP.len() - 1);
+    let channel = |c: usize| (RAMP[low][c] as f64 + (RAMP[high][c] as f64 - RAMP[low][c] as f64) * frac).round() as u8;
+    Color32::from_rgb(channel(0), channel(1), channel(2))
+}
+
+/// Position of `iteration` on the colour ramp of `span`, 0 at its start and 1 at its end
+pub fn ramp_position(iteration: u32, span: &RangeInclusive<u32>) -> f64 {
+    let (start, end) = (*span.start() as f64, *span.end() as f64);
+    (iteration as f64 - start) / (end - start).max(1.0)
+}
+
+/// The iterations of a run at which a variable has both a value and an error, as plot points
+pub struct PhaseTrajectory {
+    pub points: Vec<[f64; 2]>, // [value, error]
+    pub iterations: Vec<u32>,
+}
+
+impl PhaseTrajectory {
+    pub fn new(iterations: &[u32], values: &[f64], errors: &[f64]) -> Self {
+        let mut trajectory = PhaseTrajectory { points: Vec::new(), iterations: Vec::new() };
+        for ((&iteration, &value), &error) in iterations.iter().zip(values).zip(errors) {
+            if !value.is_nan() && !error.is_nan() {
+                trajectory.points.push([value, error]);
+                trajectory.iterations.push(iteration);
+            }
+        }
+        trajectory
+    }
+
+    /// Iterations from the earliest to the latest. Rows are not always in order, e.g. when a
+    /// followed calibration restarted.
+    pub fn span(&self) -> Option<RangeInclusive<u32>> {
+        let first = self.iterations.iter().min()?;
+        let last = self.iterations.iter().max()?;
+        Some(*first..=*last)
+    }
+
+    /// Consecutive stretches of the trajectory, each drawn in one colour, with their position on
+    /// the colour ramp of `span`. Each stretch starts where the previous one ends so the line is
+    /// unbroken.
+    pub fn bands(&self, span: &RangeInclusive<u32>) -> Vec<(&[[f64; 2]], f64)> {
+        let len = self.points.len();
+        if len < 2 {
+            return Vec::new();
+        }
+        let band_len = (len - 1).div_ceil(COLOR_BANDS);
+        (0..len - 1)
+            .step_by(band_len)
+            .map(|start| {
+                let end = (start + band_len).min(len - 1);
+                let middle = (ramp_position(self.iterations[start], span) + ramp_position(self.iterations[end], span)) / 2.0;
+                (&self.points[start..=end], middle)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_position_spans_zero_to_one() {
+        assert_eq!(ramp_position(10, &(10..=20)), 0.0);
+        assert_eq!(ramp_position(15, &(10..=20)), 0.5);
+        assert_eq!(ramp_position(20, &(10..=20)), 1.0);
+        assert_eq!(ramp_position(7, &(7..=7)), 0.0);
+    }
+
+    #[test]
+    fn non_monotonic_iterations() {
+        // A followed calibration restarted part way through
+        let iterations = [5, 6, 7, 8, 1, 2, 3];
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        let errors = [0.5, 0.4, 0.3, 0.2, 0.5, 0.4, 0.3];
+        let trajectory = PhaseTrajectory::new(&iterations, &values, &errors);
+
+        let span = trajectory.span().unwrap();
+        assert_eq!(span, 1..=8);
+        let bands = trajectory.bands(&span);
+        assert_eq!(bands.len(), iterations.len() - 1);
+        for (_, t) in &bands {
+            assert!((0.0..=1.0).contains(t), "{t} is off the ramp");
+        }
+        // The stretch across the restart sits between its ends' positions
+        assert_eq!(bands[3].1, (ramp_position(8, &span) + ramp_position(1, &span)) / 2.0);
+    }
+
+    #[test]
+    fn empty_trajectory_has_no_span() {
+        let trajectory = PhaseTrajectory::new(&[1, 2], &[f64::NAN, 1.0], &[0.1, f64::NAN]);
+        assert!(trajectory.span().is_none());
+        assert!(trajectory.bands(&(1..=2)).is_empty());
+    }
+}
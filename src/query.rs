@@ -0,0 +1,556 @@
+// Filter queries for the variable list. Terms match names by substring, glob or regex, and can be
+// negated, combined with AND/OR and parentheses, or compare a convergence metric to a number, e.g.
+// `(Auto* OR /^Transit:\d+$/) -AM final_abs_error > 0.05`.
+
+use anyhow::{Result, bail};
+use regex::Regex;
+
+use crate::convergence::VariableConvergence;
+
+/// Per-variable metrics a query can compare
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    FinalError,
+    FinalAbsError,
+    FinalValue,
+    MaxAbsError,
+    ConvergedAt,
+    Converged, // 1 if the error has settled below the tolerance, 0 otherwise
+}
+
+impl Metric {
+    pub const ALL: [Metric; 6] = [
+        Metric::FinalError,
+        Metric::FinalAbsError,
+        Metric::FinalValue,
+        Metric::MaxAbsError,
+        Metric::ConvergedAt,
+        Metric::Converged,
+    ];
+
+    /// Name of the metric in queries
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::FinalError => "final_error",
+            Metric::FinalAbsError => "final_abs_error",
+            Metric::FinalValue => "final_value",
+            Metric::MaxAbsError => "max_abs_error",
+            Metric::ConvergedAt => "converged_at",
+            Metric::Converged => "converged",
+        }
+    }
+
+    fn value(self, convergence: &VariableConvergence) -> Option<f64> {
+        match self {
+            Metric::FinalError => convergence.final_error,
+            Metric::FinalAbsError => convergence.final_error.map(f64::abs),
+            Metric::FinalValue => convergence.final_value,
+            Metric::MaxAbsError => convergence.max_abs_error,
+            Metric::ConvergedAt => convergence.converged_at.map(f64::from),
+            // Variables without errors have neither converged nor failed to
+            Metric::Converged => convergence.max_abs_error.map(|_| if convergence.converged_at.is_some() { 1.0 } else { 0.0 }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Substring(String), // Lowercase, matched case-insensitively anywhere in the name
+    Pattern(Regex), // A regex, or a glob translated to one
+    Compare(Metric, Comparison, f64),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    fn matches(&self, name: &str, lower_name: &str, metrics: Option<&VariableConvergence>) -> bool {
+        match self {
+            Expr::Substring(term) => lower_name.contains(term.as_str()),
+            Expr::Pattern(regex) => regex.is_match(name),
+            // Variables without the metric match no comparison
+            Expr::Compare(metric, comparison, right) => metrics
+                .and_then(|metrics| metric.value(metrics))
+                .is_some_and(|left| comparison.holds(left, *right)),
+            Expr::Not(expr) => !expr.matches(name, lower_name, metrics),
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.matches(name, lower_name, metrics)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.matches(name, lower_name, metrics)),
+        }
+    }
+
+    fn uses_metrics(&self) -> bool {
+        match self {
+            Expr::Substring(_) | Expr::Pattern(_) => false,
+            Expr::Compare(..) => true,
+            Expr::Not(expr) => expr.uses_metrics(),
+            Expr::And(exprs) | Expr::Or(exprs) => exprs.iter().any(Expr::uses_metrics),
+        }
+    }
+}
+
+/// A parsed filter. The empty query matches every variable.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    expr: Option<Expr>,
+}
+
+impl Query {
+    /// Parse a query. Terms next to each other must all match, `,`, `|` and `OR` separate
+    /// alternatives, and `AND` and `&` may be written out. A term is a case-insensitive substring,
+    /// a glob with `*` and `?` matched against the whole name, a `"quoted phrase"`, a `/regex/` (add
+    /// `i` after it to ignore case) or a comparison such as `converged = false`. `-` or `NOT`
+    /// negates a term and parentheses group them.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0, end: text.chars().count() };
+        if parser.tokens.is_empty() {
+            return Ok(Query::default());
+        }
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!("Unexpected {} at character {}", token.kind.describe(), token.offset + 1);
+        }
+        Ok(Query { expr: Some(expr) })
+    }
+
+    /// Whether matching needs the variables' convergence metrics
+    pub fn uses_metrics(&self) -> bool {
+        self.expr.as_ref().is_some_and(Expr::uses_metrics)
+    }
+
+    /// Whether a variable matches, given its metrics if the query uses them
+    pub fn matches(&self, name: &str, metrics: Option<&VariableConvergence>) -> bool {
+        self.expr.as_ref().is_none_or(|expr| expr.matches(name, &name.to_lowercase(), metrics))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Phrase(String),
+    Regex(String),
+    Compare(Comparison),
+    Not, // A leading `-`
+    Or, // `,` or `|`
+    And, // `&`
+    Open,
+    Close,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Word(word) => format!("\"{word}\""),
+            TokenKind::Phrase(phrase) => format!("\"{phrase}\""),
+            TokenKind::Regex(regex) => format!("/{regex}/"),
+            TokenKind::Compare(_) => "comparison".to_string(),
+            TokenKind::Not => "\"-\"".to_string(),
+            TokenKind::Or => "separator".to_string(),
+            TokenKind::And => "\"&\"".to_string(),
+            TokenKind::Open => "\"(\"".to_string(),
+            TokenKind::Close => "\")\"".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    offset: usize, // In characters, for error messages
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let offset = pos;
+        let next = chars.get(pos + 1).copied();
+        let kind = match chars[pos] {
+            c if c.is_whitespace() => {
+                pos += 1;
+                continue;
+            }
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ',' | '|' => TokenKind::Or,
+            '&' => TokenKind::And,
+            '-' => TokenKind::Not,
+            '<' | '>' | '=' | '!' if next == Some('=') => {
+                pos += 1;
+                TokenKind::Compare(match chars[offset] {
+                    '<' => Comparison::LessOrEqual,
+                    '>' => Comparison::GreaterOrEqual,
+                    '=' => Comparison::Equal,
+                    _ => Comparison::NotEqual,
+                })
+            }
+            '<' => TokenKind::Compare(Comparison::Less),
+            '>' => TokenKind::Compare(Comparison::Greater),
+            '=' => TokenKind::Compare(Comparison::Equal),
+            quote @ ('"' | '/') => {
+                // Read up to the closing quote, a backslash keeps a slash inside a regex
+                let mut content = String::new();
+                pos += 1;
+                loop {
+                    match chars.get(pos) {
+                        None if quote == '"' => bail!("Unclosed quote at character {}", offset + 1),
+                        None => bail!("Unclosed regex at character {}", offset + 1),
+                        Some(&c) if c == quote => break,
+                        Some('\\') if quote == '/' && chars.get(pos + 1) == Some(&'/') => {
+                            content.push('/');
+                            pos += 2;
+                        }
+                        Some(&c) => {
+                            content.push(c);
+                            pos += 1;
+                        }
+                    }
+                }
+                if quote == '"' {
+                    TokenKind::Phrase(content)
+                } else if chars.get(pos + 1) == Some(&'i') && chars.get(pos + 2).is_none_or(|&c| ends_word(c, None)) {
+                    pos += 1;
+                    TokenKind::Regex(format!("(?i){content}"))
+                } else {
+                    TokenKind::Regex(content)
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.get(pos) {
+                    if ends_word(c, chars.get(pos + 1).copied()) {
+                        break;
+                    }
+                    word.push(c);
+                    pos += 1;
+                }
+                tokens.push(Token { kind: TokenKind::Word(word), offset });
+                continue;
+            }
+        };
+        tokens.push(Token { kind, offset });
+        pos += 1;
+    }
+    Ok(tokens)
+}
+
+/// Whether `c` (followed by `next`) ends a word. Dashes and slashes inside words are kept, so
+/// `AutoOwnership-1` is one term.
+fn ends_word(c: char, next: Option<char>) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | ',' | '|' | '&' | '<' | '>' | '=' | '"') || (c == '!' && next == Some('='))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize, // Length of the query, where errors at the end point
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|token| &token.kind)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |token| token.offset)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(TokenKind::Word(word)) if word == keyword)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut alternatives = vec![self.and()?];
+        while matches!(self.peek(), Some(TokenKind::Or)) || self.is_keyword("OR") {
+            self.pos += 1;
+            alternatives.push(self.and()?);
+        }
+        Ok(if alternatives.len() == 1 { alternatives.remove(0) } else { Expr::Or(alternatives) })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut terms = vec![self.unary()?];
+        loop {
+            if matches!(self.peek(), Some(TokenKind::And)) || self.is_keyword("AND") {
+                self.pos += 1;
+            } else if !self.starts_term() {
+                break;
+            }
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Expr::And(terms) })
+    }
+
+    fn starts_term(&self) -> bool {
+        match self.peek() {
+            Some(TokenKind::Word(word)) => word != "OR" && word != "AND",
+            Some(TokenKind::Phrase(_) | TokenKind::Regex(_) | TokenKind::Not | TokenKind::Open) => true,
+            _ => false,
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if matches!(self.peek(), Some(TokenKind::Not)) || self.is_keyword("NOT") {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.term()
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        let Some(kind) = self.peek().cloned() else {
+            bail!("Expected a term at the end of the query");
+        };
+        self.pos += 1;
+        match kind {
+            TokenKind::Open => {
+                let expr = self.or()?;
+                if !matches!(self.peek(), Some(TokenKind::Close)) {
+                    bail!("Missing \")\" for the \"(\" at character {}", offset + 1);
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            TokenKind::Phrase(phrase) => Ok(Expr::Substring(phrase.to_lowercase())),
+            TokenKind::Regex(pattern) => match Regex::new(&pattern) {
+                Ok(regex) => Ok(Expr::Pattern(regex)),
+                Err(e) => {
+                    // The regex error spans several lines, pointing into the pattern
+                    let e = e.to_string();
+                    let reason = e.lines().last().unwrap_or_default().trim_start_matches("error: ");
+                    bail!("Invalid regex at character {}: {reason}", offset + 1)
+                }
+            },
+            TokenKind::Word(word) if word == "AND" || word == "OR" => {
+                bail!("Expected a term at character {}, found {word}", offset + 1)
+            }
+            TokenKind::Word(word) => {
+                if let Some(&TokenKind::Compare(comparison)) = self.peek() {
+                    self.pos += 1;
+                    return self.comparison(&word, offset, comparison);
+                }
+                if word.contains(['*', '?']) {
+                    Ok(Expr::Pattern(glob(&word)))
+                } else {
+                    Ok(Expr::Substring(word.to_lowercase()))
+                }
+            }
+            other => bail!("Expected a term at character {}, found {}", offset + 1, other.describe()),
+        }
+    }
+
+    fn comparison(&mut self, name: &str, offset: usize, comparison: Comparison) -> Result<Expr> {
+        let Some(metric) = Metric::ALL.into_iter().find(|metric| metric.name().eq_ignore_ascii_case(name)) else {
+            let names: Vec<&str> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+            bail!("Unknown metric \"{name}\" at character {}, expected one of {}", offset + 1, names.join(", "));
+        };
+        let value_offset = self.offset();
+        let negative = matches!(self.peek(), Some(TokenKind::Not));
+        if negative {
+            self.pos += 1;
+        }
+        let value = match self.peek() {
+            Some(TokenKind::Word(word)) if !negative && word.eq_ignore_ascii_case("true") => Some(1.0),
+            Some(TokenKind::Word(word)) if !negative && word.eq_ignore_ascii_case("false") => Some(0.0),
+            Some(TokenKind::Word(word)) => word.parse::<f64>().ok().filter(|val| val.is_finite()),
+            _ => None,
+        };
+        let Some(value) = value else {
+            bail!("Expected a number after {} at character {}", metric.name(), value_offset + 1);
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(metric, comparison, if negative { -value } else { value }))
+    }
+}
+
+/// A regex matching whole names against a glob, ignoring case
+fn glob(pattern: &str) -> Regex {
+    let mut regex = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).expect("escaped glob is a valid regex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 6] = [
+        "AutoOwnership-1",
+        "AutoOwnership-2",
+        "ModeChoice:Transit:AM",
+        "ModeChoice:Auto:AM",
+        "ModeChoice:Transit:PM",
+        "Transit-10",
+    ];
+
+    /// Names matched by `query`, which must parse
+    fn matching(query: &str) -> Vec<&'static str> {
+        let query = Query::parse(query).unwrap();
+        NAMES.into_iter().filter(|name| query.matches(name, None)).collect()
+    }
+
+    fn error(query: &str) -> String {
+        Query::parse(query).unwrap_err().to_string()
+    }
+
+    fn convergence(final_error: f64, converged_at: Option<u32>) -> VariableConvergence {
+        VariableConvergence {
+            var: 0,
+            converged_at,
+            final_error: Some(final_error),
+            final_value: Some(100.0),
+            max_abs_error: Some(final_error.abs().max(0.5)),
+        }
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(matching(""), NAMES);
+        assert_eq!(matching("   "), NAMES);
+    }
+
+    #[test]
+    fn substring_ignores_case() {
+        assert_eq!(matching("transit"), ["ModeChoice:Transit:AM", "ModeChoice:Transit:PM", "Transit-10"]);
+        assert_eq!(matching("ownership-2"), ["AutoOwnership-2"]);
+    }
+
+    #[test]
+    fn alternatives() {
+        let expected = ["AutoOwnership-1", "AutoOwnership-2", "Transit-10"];
+        assert_eq!(matching("Ownership,Transit-"), expected);
+        assert_eq!(matching("Ownership | Transit-"), expected);
+        assert_eq!(matching("Ownership OR Transit-"), expected);
+    }
+
+    #[test]
+    fn spaces_mean_and() {
+        let expected = ["ModeChoice:Transit:PM"];
+        assert_eq!(matching("transit pm"), expected);
+        assert_eq!(matching("transit AND pm"), expected);
+        assert_eq!(matching("transit & pm"), expected);
+    }
+
+    #[test]
+    fn exclusion() {
+        let expected = ["AutoOwnership-1", "AutoOwnership-2", "ModeChoice:Auto:AM"];
+        assert_eq!(matching("Auto -Transit"), expected);
+        assert_eq!(matching("Auto NOT Transit"), expected);
+        assert_eq!(matching("-(Auto | Transit)"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(matching("ModeChoice (AM | PM) -Auto"), ["ModeChoice:Transit:AM", "ModeChoice:Transit:PM"]);
+        assert_eq!(matching("(Ownership, Auto) AM"), ["ModeChoice:Auto:AM"]);
+    }
+
+    #[test]
+    fn globs_match_whole_names() {
+        assert_eq!(matching("Auto*-?"), ["AutoOwnership-1", "AutoOwnership-2"]);
+        assert_eq!(matching("*:am"), ["ModeChoice:Transit:AM", "ModeChoice:Auto:AM"]);
+        assert_eq!(matching("Transit*"), ["Transit-10"]);
+    }
+
+    #[test]
+    fn regexes() {
+        assert_eq!(matching("/^auto/"), Vec::<&str>::new());
+        assert_eq!(matching("/^auto/i"), ["AutoOwnership-1", "AutoOwnership-2"]);
+        assert_eq!(matching(r"/^Transit-\d+$/"), ["Transit-10"]);
+        assert_eq!(matching(r"/:Auto\/?/"), ["ModeChoice:Auto:AM"]);
+    }
+
+    #[test]
+    fn quoted_phrases() {
+        assert_eq!(matching("\"transit:am\""), ["ModeChoice:Transit:AM"]);
+        // Operators inside quotes are part of the phrase
+        assert_eq!(matching("\"-1\""), ["AutoOwnership-1", "Transit-10"]);
+        assert_eq!(matching("\"(am | pm)\""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn metric_comparisons() {
+        let converged = convergence(-0.01, Some(12));
+        let off = convergence(0.2, None);
+        let query = |text: &str| Query::parse(text).unwrap();
+
+        assert!(query("final_abs_error < 0.05").matches("a", Some(&converged)));
+        assert!(!query("final_abs_error < 0.05").matches("a", Some(&off)));
+        assert!(query("final_error >= -0.01").matches("a", Some(&converged)));
+        assert!(!query("final_error > -0.01").matches("a", Some(&converged)));
+        assert!(query("converged_at <= 12").matches("a", Some(&converged)));
+        assert!(query("converged = true").matches("a", Some(&converged)));
+        assert!(query("converged == false").matches("a", Some(&off)));
+        assert!(query("converged != true").matches("a", Some(&off)));
+        assert!(query("FINAL_VALUE = 100").matches("a", Some(&off)));
+        assert!(query("max_abs_error > 0.4").matches("a", Some(&off)));
+        // Variables without the metric match no comparison
+        assert!(!query("converged_at > 0").matches("a", Some(&off)));
+        assert!(!query("final_error < 1").matches("a", None));
+        assert!(query("-(final_error < 1)").matches("a", None));
+    }
+
+    #[test]
+    fn metrics_combine_with_names() {
+        let query = Query::parse("Auto final_abs_error > 0.1").unwrap();
+        assert!(query.matches("AutoOwnership-1", Some(&convergence(0.2, None))));
+        assert!(!query.matches("AutoOwnership-1", Some(&convergence(0.01, Some(3)))));
+        assert!(!query.matches("Transit-10", Some(&convergence(0.2, None))));
+    }
+
+    #[test]
+    fn uses_metrics() {
+        assert!(!Query::parse("").unwrap().uses_metrics());
+        assert!(!Query::parse("Auto -/AM$/ \"x y\"").unwrap().uses_metrics());
+        assert!(Query::parse("Auto | -(converged = false)").unwrap().uses_metrics());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(error("auto,"), "Expected a term at the end of the query");
+        assert_eq!(error("auto &"), "Expected a term at the end of the query");
+        assert_eq!(error("(auto"), "Missing \")\" for the \"(\" at character 1");
+        assert_eq!(error("a )"), "Unexpected \")\" at character 3");
+        assert_eq!(error("/abc"), "Unclosed regex at character 1");
+        assert_eq!(error("\"abc"), "Unclosed quote at character 1");
+        assert_eq!(error("/(/"), "Invalid regex at character 1: unclosed group");
+        assert_eq!(error("AND"), "Expected a term at character 1, found AND");
+        assert_eq!(
+            error("foo > 1"),
+            "Unknown metric \"foo\" at character 1, expected one of final_error, final_abs_error, final_value, max_abs_error, converged_at, converged",
+        );
+        assert_eq!(error("final_abs_error >"), "Expected a number after final_abs_error at character 18");
+        assert_eq!(error("converged = maybe"), "Expected a number after converged at character 13");
+    }
+}
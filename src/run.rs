@@ -0,0 +1,209 @@
+// A calibration report loaded into the app. Several runs can be loaded at once so that a new
+// calibration can be compared against earlier ones on the same plots.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::columnar::ReportFormat;
+use crate::compression::Compression;
+use crate::data::{CalibrationData, LoadedReport};
+use crate::dialect::Dialect;
+use crate::schema::ColumnSchema;
+
+/// How a run's lines are drawn, so runs can be told apart when their variables share a colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunLineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl RunLineStyle {
+    pub const ALL: [RunLineStyle; 3] = [RunLineStyle::Solid, RunLineStyle::Dashed, RunLineStyle::Dotted];
+
+    /// Style for the n-th loaded run
+    pub fn nth(n: usize) -> Self {
+        Self::ALL[n % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RunLineStyle::Solid => "Solid",
+            RunLineStyle::Dashed => "Dashed",
+            RunLineStyle::Dotted => "Dotted",
+        }
+    }
+
+    pub fn plot_style(self) -> egui_plot::LineStyle {
+        match self {
+            RunLineStyle::Solid => egui_plot::LineStyle::Solid,
+            RunLineStyle::Dashed => egui_plot::LineStyle::dashed_loose(),
+            RunLineStyle::Dotted => egui_plot::LineStyle::dotted_dense(),
+        }
+    }
+}
+
+/// Where the last parse of the file stopped, so follow mode only reads appended rows
+#[derive(Debug, Default)]
+struct TailState {
+    offset: u64, // Byte offset where the next poll starts parsing
+    file_len: u64, // File length when last parsed, used to detect growth/truncation
+    line: usize, // Line number of the row at offset, for error messages
+    pending_row: bool, // The last record came from a row without a line ending
+}
+
+/// Result of checking a followed file for new rows
+#[derive(Debug, PartialEq, Eq)]
+pub enum TailStatus {
+    Unchanged,
+    Appended,
+    NewVariables, // Rows were appended and named variables not seen before (long format)
+    Truncated, // The file shrank, or a compressed or columnar file changed, and has to be loaded again from scratch
+}
+
+#[derive(Debug)]
+pub struct Run {
+    pub id: u64,
+    pub label: String,
+    pub path: String,
+    pub data: CalibrationData,
+    pub schema: ColumnSchema, // Schema the report was loaded with
+    pub dialect: Dialect, // Format the report was read with, reused for appended rows
+    pub compression: Compression,
+    pub format: ReportFormat,
+    pub line_style: RunLineStyle,
+    pub var_ids: Vec<Option<usize>>, // App-wide variable id -> id within this run's data
+    pub generation: u64, // Bumped whenever `data` changes, so caches built from it know to rebuild
+    tail: TailState,
+}
+
+impl Run {
+    pub fn new(id: u64, report: LoadedReport, line_style: RunLineStyle) -> Self {
+        let label = Path::new(&report.path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| report.path.clone());
+
+        let mut run = Run {
+            id,
+            label,
+            path: String::new(),
+            data: CalibrationData::default(),
+            schema: ColumnSchema::default(),
+            dialect: Dialect::default(),
+            compression: Compression::None,
+            format: ReportFormat::Csv,
+            line_style,
+            var_ids: Vec::new(),
+            generation: 0,
+            tail: TailState::default(),
+        };
+        run.replace_report(report);
+        run
+    }
+
+    /// Swap in freshly loaded data, keeping the label and line style
+    pub fn replace_report(&mut self, report: LoadedReport) {
+        self.path = report.path;
+        self.data = report.data;
+        self.schema = report.schema;
+        self.dialect = report.dialect;
+        self.compression = report.compression;
+        self.format = report.format;
+        self.generation += 1;
+        self.tail = TailState {
+            offset: report.complete_len,
+            file_len: report.file_len,
+            line: report.next_line,
+            pending_row: report.pending_row,
+        };
+    }
+
+    /// Whether the run was read from delimited text, which the delimiter, encoding and decimal
+    /// settings apply to
+    pub fn is_text(&self) -> bool {
+        self.format == ReportFormat::Csv
+    }
+
+    /// Samples of one kind of an app-wide variable id, if this run has them
+    pub fn series(&self, kind: &str, var: usize) -> Option<&[f64]> {
+        self.var_ids.get(var).copied().flatten().and_then(|id| self.data.series(kind, id))
+    }
+
+    /// Error samples of an app-wide variable id, if this run has them
+    pub fn error_series(&self, var: usize) -> Option<&[f64]> {
+        self.var_ids.get(var).copied().flatten().and_then(|id| self.data.error_series(id))
+    }
+
+    /// Value samples of an app-wide variable id, if this run has them
+    pub fn value_series(&self, var: usize) -> Option<&[f64]> {
+        self.var_ids.get(var).copied().flatten().and_then(|id| self.data.value_series(id))
+    }
+
+    /// Parse the rows appended to the file since the last load or poll
+    pub fn poll_tail(&mut self) -> Result<TailStatus> {
+        let file_len = std::fs::metadata(&self.path)
+            .with_context(|| format!("Failed to read metadata: {}", self.path))?
+            .len();
+
+        if file_len < self.tail.file_len {
+            // The report was rewritten (e.g. a new calibration started)
+            return Ok(TailStatus::Truncated);
+        }
+        let appendable = self.format == ReportFormat::Csv && self.compression == Compression::None;
+        if !appendable && file_len != self.tail.file_len {
+            // Compressed and columnar files cannot be picked up part way through
+            return Ok(TailStatus::Truncated);
+        }
+        if file_len == self.tail.file_len {
+            return Ok(TailStatus::Unchanged);
+        }
+
+        let mut file = File::open(&self.path)
+            .with_context(|| format!("Failed to open file: {}", self.path))?;
+        file.seek(SeekFrom::Start(self.tail.offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+        let read_len = self.tail.offset + appended.len() as u64;
+
+        // Only parse whole lines, the writer may be part way through the last one
+        let Some(complete_len) = self.dialect.last_line_end(&appended, self.tail.offset) else {
+            self.tail.file_len = read_len;
+            return Ok(TailStatus::Unchanged);
+        };
+        let complete = &appended[..complete_len];
+
+        // Parse every appended row before keeping any. If one fails, nothing moves on and the
+        // rows are parsed again on the next poll.
+        let mut rdr = self.dialect.csv_reader(complete, false);
+        let mut rows = Vec::new();
+        let mut line = self.tail.line;
+        for row in rdr.records() {
+            rows.push(row.with_context(|| format!("Failed to parse CSV record at line {line}"))?);
+            line += 1;
+        }
+
+        // The unterminated row kept by the initial load is about to be re-read in full
+        if self.tail.pending_row {
+            self.data.pop_row();
+            self.tail.pending_row = false;
+        }
+
+        let variable_count = self.data.variable_names.len();
+        for row in &rows {
+            self.data.push_row(row);
+        }
+        self.generation += 1;
+        self.tail.line = line;
+        self.tail.offset += complete.len() as u64;
+        self.tail.file_len = read_len;
+
+        if self.data.variable_names.len() != variable_count {
+            Ok(TailStatus::NewVariables)
+        } else {
+            Ok(TailStatus::Appended)
+        }
+    }
+}
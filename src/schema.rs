@@ -0,0 +1,139 @@
+// Which CSV columns hold which kind of series. Error and Value are always present, further kinds
+// (e.g. the Target:, Parameter: or Weight: columns some modules write) can be added as rules.
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+pub const ERROR: &str = "Error";
+pub const VALUE: &str = "Value";
+
+/// How a rule recognises its columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOn {
+    Prefix,
+    Suffix,
+    Regex, // Base name is the first capture group, or the header without the match
+}
+
+impl MatchOn {
+    pub const ALL: [MatchOn; 3] = [MatchOn::Prefix, MatchOn::Suffix, MatchOn::Regex];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchOn::Prefix => "Prefix",
+            MatchOn::Suffix => "Suffix",
+            MatchOn::Regex => "Regex",
+        }
+    }
+}
+
+/// Columns matching `pattern` hold `kind` samples of the variable named by the rest of the header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesRule {
+    pub kind: String,
+    pub match_on: MatchOn,
+    pub pattern: String,
+}
+
+impl SeriesRule {
+    pub fn new(kind: &str, match_on: MatchOn, pattern: &str) -> Self {
+        SeriesRule { kind: kind.to_string(), match_on, pattern: pattern.to_string() }
+    }
+
+    fn compile(&self) -> Result<Matcher> {
+        if self.kind.trim().is_empty() {
+            bail!("A series kind needs a name");
+        }
+        if self.pattern.is_empty() {
+            bail!("The {} pattern of \"{}\" is empty", self.match_on.label().to_lowercase(), self.kind);
+        }
+        Ok(match self.match_on {
+            MatchOn::Prefix => Matcher::Prefix(self.pattern.clone()),
+            MatchOn::Suffix => Matcher::Suffix(self.pattern.clone()),
+            MatchOn::Regex => Matcher::Regex(
+                Regex::new(&self.pattern).with_context(|| format!("Invalid regex for \"{}\"", self.kind))?,
+            ),
+        })
+    }
+
+    /// Why this rule cannot be used, for showing next to it in the editor
+    pub fn error(&self) -> Option<String> {
+        self.compile().err().map(|e| format!("{e:#}"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub rules: Vec<SeriesRule>, // Checked in order, the first match wins
+}
+
+impl Default for ColumnSchema {
+    fn default() -> Self {
+        ColumnSchema {
+            rules: vec![
+                SeriesRule::new(ERROR, MatchOn::Prefix, "Error:"),
+                SeriesRule::new(VALUE, MatchOn::Prefix, "Value:"),
+            ],
+        }
+    }
+}
+
+impl ColumnSchema {
+    pub fn compile(&self) -> Result<ColumnMatcher> {
+        let mut matcher = ColumnMatcher { kinds: vec![ERROR.to_string(), VALUE.to_string()], rules: Vec::new() };
+        for rule in &self.rules {
+            let kind_name = rule.kind.trim();
+            let kind = match matcher.kinds.iter().position(|k| k == kind_name) {
+                Some(kind) => kind,
+                None => {
+                    matcher.kinds.push(kind_name.to_string());
+                    matcher.kinds.len() - 1
+                }
+            };
+            matcher.rules.push((kind, rule.compile()?));
+        }
+        Ok(matcher)
+    }
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Prefix(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+/// A schema ready to be applied to headers
+#[derive(Debug)]
+pub struct ColumnMatcher {
+    kinds: Vec<String>, // Error and Value first, then the other kinds in rule order
+    rules: Vec<(usize, Matcher)>,
+}
+
+impl ColumnMatcher {
+    pub fn kinds(&self) -> &[String] {
+        &self.kinds
+    }
+
+    /// Kind index and base variable name of a column, if any rule matches its header
+    pub fn match_header(&self, header: &str) -> Option<(usize, String)> {
+        self.rules.iter().find_map(|(kind, matcher)| {
+            let base_name = match matcher {
+                Matcher::Prefix(prefix) => header.strip_prefix(prefix.as_str())?.to_string(),
+                Matcher::Suffix(suffix) => header.strip_suffix(suffix.as_str())?.to_string(),
+                Matcher::Regex(regex) => {
+                    let captures = regex.captures(header)?;
+                    match captures.get(1) {
+                        Some(group) => group.as_str().to_string(),
+                        None => {
+                            let whole = captures.get(0)?;
+                            format!("{}{}", &header[..whole.start()], &header[whole.end()..])
+                        }
+                    }
+                }
+            };
+            let base_name = base_name.trim();
+            (!base_name.is_empty()).then(|| (*kind, base_name.to_string()))
+        })
+    }
+}
@@ -0,0 +1,92 @@
+// Smoothing of noisy series, e.g. the Value trajectories of stochastic calibrations. The smoothed
+// line is drawn over the faded raw one and exported next to it.
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SmoothingKind {
+    #[default]
+    None,
+    MovingAverage, // Mean of a trailing window of iterations
+    Ewma, // Exponentially weighted moving average
+}
+
+impl SmoothingKind {
+    pub const ALL: [SmoothingKind; 3] = [SmoothingKind::None, SmoothingKind::MovingAverage, SmoothingKind::Ewma];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SmoothingKind::None => "None",
+            SmoothingKind::MovingAverage => "Moving average",
+            SmoothingKind::Ewma => "EWMA",
+        }
+    }
+}
+
+/// Smoothing of one plot, keeping the parameters of both kinds so switching back and forth does
+/// not lose them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Smoothing {
+    pub kind: SmoothingKind,
+    pub window: usize, // Iterations averaged by the moving average
+    pub alpha: f64, // Weight of the newest sample in the EWMA
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Smoothing { kind: SmoothingKind::None, window: 5, alpha: 0.3 }
+    }
+}
+
+impl Smoothing {
+    pub fn is_enabled(&self) -> bool {
+        self.kind != SmoothingKind::None
+    }
+
+    /// Short description for legends and column names, e.g. "MA(5)" or "EWMA(0.3)"
+    pub fn label(&self) -> String {
+        match self.kind {
+            SmoothingKind::None => String::new(),
+            SmoothingKind::MovingAverage => format!("MA({})", self.window),
+            SmoothingKind::Ewma => format!("EWMA({})", self.alpha),
+        }
+    }
+
+    /// Smoothed copy of `samples`. Missing samples stay missing and are left out of the averages
+    /// around them.
+    pub fn apply(&self, samples: &[f64]) -> Vec<f64> {
+        match self.kind {
+            SmoothingKind::None => samples.to_vec(),
+            SmoothingKind::MovingAverage => {
+                let window = self.window.max(1);
+                let (mut sum, mut count) = (0.0, 0usize);
+                let mut smoothed = Vec::with_capacity(samples.len());
+                for (i, &val) in samples.iter().enumerate() {
+                    if !val.is_nan() {
+                        sum += val;
+                        count += 1;
+                    }
+                    if i >= window && !samples[i - window].is_nan() {
+                        sum -= samples[i - window];
+                        count -= 1;
+                    }
+                    smoothed.push(if val.is_nan() || count == 0 { f64::NAN } else { sum / count as f64 });
+                }
+                smoothed
+            }
+            SmoothingKind::Ewma => {
+                let alpha = self.alpha.clamp(0.0, 1.0);
+                let mut average: Option<f64> = None;
+                samples
+                    .iter()
+                    .map(|&val| {
+                        if val.is_nan() {
+                            return f64::NAN;
+                        }
+                        let next = average.map_or(val, |average| alpha * val + (1.0 - alpha) * average);
+                        average = Some(next);
+                        next
+                    })
+                    .collect()
+            }
+        }
+    }
+}